@@ -0,0 +1,51 @@
+//! Compares popcount-based base counting against a per-base scan.
+//!
+//! Run with `cargo bench -p dna --bench count`.
+
+use std::time::Instant;
+
+use dna::{packed::PackedDna, Nuc};
+
+const LEN: usize = 1_000_000;
+const ROUNDS: u32 = 50;
+
+fn main() {
+    let dna: PackedDna = b"ACGTTGCAGGC"
+        .iter()
+        .cycle()
+        .take(LEN)
+        .map(|&b| match b {
+            b'A' => Nuc::A,
+            b'C' => Nuc::C,
+            b'G' => Nuc::G,
+            _ => Nuc::T,
+        })
+        .collect();
+
+    let start = Instant::now();
+    let mut naive = 0;
+    for _ in 0..ROUNDS {
+        naive = black_box(&dna).iter().filter(|&nuc| nuc == Nuc::G).count();
+    }
+    report("per-base", start.elapsed().as_secs_f64());
+
+    let start = Instant::now();
+    let mut popcount = 0;
+    for _ in 0..ROUNDS {
+        popcount = black_box(&dna).count(Nuc::G);
+    }
+    report("popcount", start.elapsed().as_secs_f64());
+    assert_eq!(naive, popcount);
+}
+
+/// Hides `value` from the optimiser, as `std::hint::black_box` (which needs
+/// Rust 1.66) would.
+fn black_box<T: Copy>(value: T) -> T {
+    // SAFETY: `value` is a valid, initialised `T` for the whole read
+    unsafe { std::ptr::read_volatile(&value) }
+}
+
+fn report(name: &str, secs: f64) {
+    let bases = (LEN as f64) * f64::from(ROUNDS);
+    println!("{:>10}: {:8.1} Mbases/s", name, bases / secs / 1e6);
+}
@@ -0,0 +1,38 @@
+//! Compares the lookup-table parser against a per-char parse.
+//!
+//! Run with `cargo bench -p dna --bench parse`.
+
+use std::{convert::TryFrom, time::Instant};
+
+use dna::{packed::PackedDna, Nuc};
+
+const LEN: usize = 1_000_000;
+const ROUNDS: u32 = 20;
+
+fn main() {
+    let input: String = b"ACGT"
+        .iter()
+        .cycle()
+        .take(LEN)
+        .map(|&b| char::from(b))
+        .collect();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let dna: Result<PackedDna, _> = input.chars().map(Nuc::try_from).collect();
+        assert_eq!(dna.unwrap().len(), LEN);
+    }
+    report("per-char", start.elapsed().as_secs_f64());
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        let dna = PackedDna::from_ascii(input.as_bytes()).unwrap();
+        assert_eq!(dna.len(), LEN);
+    }
+    report("from_ascii", start.elapsed().as_secs_f64());
+}
+
+fn report(name: &str, secs: f64) {
+    let bases = (LEN as f64) * f64::from(ROUNDS);
+    println!("{:>10}: {:8.1} Mbases/s", name, bases / secs / 1e6);
+}
@@ -0,0 +1,258 @@
+//! Reading FASTA records into packed sequences.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use crate::packed::{PackedDna, ParseDnaError};
+
+/// The suffix [`FastaRecord::reverse_complement`] appends to the header.
+pub const REVERSE_COMPLEMENT_SUFFIX: &str = " (reverse complement)";
+
+/// A single FASTA record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FastaRecord {
+    header: String,
+    seq: PackedDna,
+}
+
+impl FastaRecord {
+    /// Creates a record from a header (without the leading `>`) and a
+    /// sequence.
+    pub fn new(header: impl Into<String>, seq: PackedDna) -> Self {
+        Self {
+            header: header.into(),
+            seq,
+        }
+    }
+
+    /// The record's header line, without the leading `>`.
+    pub fn header(&self) -> &str {
+        &self.header
+    }
+
+    /// The record's sequence.
+    pub fn seq(&self) -> &PackedDna {
+        &self.seq
+    }
+
+    /// Replaces the sequence with its reverse complement and appends
+    /// [`REVERSE_COMPLEMENT_SUFFIX`] to the header.
+    pub fn reverse_complement(&mut self) {
+        self.reverse_complement_with_suffix(REVERSE_COMPLEMENT_SUFFIX);
+    }
+
+    /// Replaces the sequence with its reverse complement and appends `suffix`
+    /// to the header.
+    pub fn reverse_complement_with_suffix(&mut self, suffix: &str) {
+        self.seq = self.seq.reverse_complement();
+        self.header.push_str(suffix);
+    }
+}
+
+/// An error that can occur when reading FASTA records.
+#[derive(Debug, thiserror::Error)]
+pub enum FastaError {
+    /// The underlying reader failed, or the file could not be opened.
+    #[error("failed to read FASTA input")]
+    Io(#[from] io::Error),
+    /// Sequence data appeared before the first `>` header.
+    #[error("line {0}: expected a header starting with '>'")]
+    Header(usize),
+    /// A sequence line contained something other than A, C, G or T.
+    #[error("line {line}: invalid sequence")]
+    Sequence {
+        /// The line of the sequence.
+        line: usize,
+        /// The underlying parse failure, indexed within the line.
+        #[source]
+        source: ParseDnaError,
+    },
+}
+
+/// Reads FASTA records from `reader`.
+///
+/// A record is a `>` header line followed by any number of sequence lines,
+/// which are joined. Blank lines are skipped.
+///
+/// A read failure partway through a record is reported in place of that
+/// record. After an invalid sequence line, the rest of its record is skipped
+/// so that iteration resumes at the next header.
+pub fn read_fasta<R: BufRead>(reader: R) -> impl Iterator<Item = Result<FastaRecord, FastaError>> {
+    let mut lines = reader
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line))
+        .peekable();
+    std::iter::from_fn(move || {
+        let (header_no, header) = loop {
+            match lines.next()? {
+                (_, Ok(line)) if line.trim().is_empty() => continue,
+                (line_no, Ok(line)) => break (line_no, line),
+                (_, Err(err)) => return Some(Err(err.into())),
+            }
+        };
+        let header = match header.strip_prefix('>') {
+            Some(header) => header.trim_end().to_owned(),
+            None => return Some(Err(FastaError::Header(header_no))),
+        };
+        let mut seq = PackedDna::new();
+        loop {
+            match lines.peek() {
+                None => break,
+                Some((_, Ok(line))) if line.starts_with('>') => break,
+                _ => {}
+            }
+            let (line_no, line) = lines.next().expect("peeked");
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+            match PackedDna::from_ascii(line.trim().as_bytes()) {
+                Ok(part) => seq.append(&part),
+                Err(source) => {
+                    // skip to the next header; a read failure is left for the
+                    // next call to report
+                    while let Some((_, Ok(line))) = lines.peek() {
+                        if line.starts_with('>') {
+                            break;
+                        }
+                        lines.next();
+                    }
+                    return Some(Err(FastaError::Sequence {
+                        line: line_no,
+                        source,
+                    }));
+                }
+            }
+        }
+        Some(Ok(FastaRecord { header, seq }))
+    })
+}
+
+/// Reads every record of the FASTA file at `path`.
+pub fn from_fasta_path<P: AsRef<Path>>(path: P) -> Result<Vec<FastaRecord>, FastaError> {
+    read_fasta(BufReader::new(File::open(path)?)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn dna(s: &str) -> PackedDna {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn reads_multiline_records() {
+        let input = ">seq1 first\nACGT\nAC\n\n>seq2\ngg\n";
+        let records: Vec<_> = read_fasta(input.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            records,
+            vec![
+                FastaRecord::new("seq1 first", dna("ACGTAC")),
+                FastaRecord::new("seq2", dna("GG")),
+            ]
+        );
+    }
+
+    #[test]
+    fn reverse_complement_record() {
+        let mut record = FastaRecord::new("chr1", dna("AACGT"));
+        record.reverse_complement();
+        assert_eq!(record.seq(), &dna("ACGTT"));
+        assert_eq!(record.header(), "chr1 (reverse complement)");
+
+        let mut record = FastaRecord::new("read7", dna("GGA"));
+        record.reverse_complement_with_suffix("/rc");
+        assert_eq!(record.seq(), &dna("TCC"));
+        assert_eq!(record.header(), "read7/rc");
+    }
+
+    #[test]
+    fn rejects_sequence_before_header() {
+        let mut records = read_fasta("ACGT\n>seq\n".as_bytes());
+        assert!(matches!(records.next(), Some(Err(FastaError::Header(1)))));
+    }
+
+    #[test]
+    fn rejects_invalid_bases() {
+        let mut records = read_fasta(">seq\nACGT\nACNT\n".as_bytes());
+        match records.next() {
+            Some(Err(FastaError::Sequence { line, source })) => {
+                assert_eq!((line, source.index()), (3, 2));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn resumes_at_next_header_after_invalid_bases() {
+        let input = ">bad\nACGT\nACNT\nGGGG\n>good\nTTA\n";
+        let records: Vec<_> = read_fasta(input.as_bytes()).collect();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(
+            records[0],
+            Err(FastaError::Sequence { line: 3, .. })
+        ));
+        let good = records[1].as_ref().unwrap();
+        assert_eq!((good.header(), good.seq()), ("good", &dna("TTA")));
+    }
+
+    /// Yields `data`, then fails once, then reports end of input.
+    struct FailingReader {
+        data: &'static [u8],
+        failed: bool,
+    }
+
+    impl io::Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if !self.data.is_empty() {
+                let n = self.data.len().min(buf.len());
+                buf[..n].copy_from_slice(&self.data[..n]);
+                self.data = &self.data[n..];
+                Ok(n)
+            } else if !self.failed {
+                self.failed = true;
+                Err(io::Error::other("disk on fire"))
+            } else {
+                Ok(0)
+            }
+        }
+    }
+
+    #[test]
+    fn reports_read_failure_mid_record() {
+        let reader = BufReader::new(FailingReader {
+            data: b">seq\nACGT\n",
+            failed: false,
+        });
+        let mut records = read_fasta(reader);
+        assert!(matches!(records.next(), Some(Err(FastaError::Io(_)))));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn from_fasta_path_reads_file() {
+        let path = std::env::temp_dir().join(format!("dna-fasta-test-{}.fa", std::process::id()));
+        fs::write(&path, ">a\nACGT\n>b\nTTGA\nC\n").unwrap();
+        let records = from_fasta_path(&path);
+        fs::remove_file(&path).unwrap();
+        let records = records.unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].header(), "b");
+        assert_eq!(records[1].seq(), &dna("TTGAC"));
+    }
+
+    #[test]
+    fn from_fasta_path_missing_file() {
+        let path = std::env::temp_dir().join("dna-fasta-test-does-not-exist.fa");
+        assert!(matches!(from_fasta_path(path), Err(FastaError::Io(_))));
+    }
+}
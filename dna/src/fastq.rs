@@ -0,0 +1,283 @@
+//! Reading FASTQ records into packed sequences.
+
+use std::{
+    io::{self, BufRead},
+    iter::Peekable,
+};
+
+use crate::packed::{LengthMismatch, PackedDna, ParseDnaError};
+
+/// The offset of Phred scores in FASTQ quality characters (Sanger encoding).
+const PHRED_OFFSET: u8 = b'!';
+
+/// A single FASTQ record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FastqRecord {
+    id: String,
+    seq: PackedDna,
+    qual: Vec<u8>,
+}
+
+impl FastqRecord {
+    /// Creates a record from an id (without the leading `@`), a sequence and
+    /// its decoded Phred quality scores.
+    ///
+    /// Fails unless there is exactly one quality score per base.
+    pub fn new(
+        id: impl Into<String>,
+        seq: PackedDna,
+        qual: Vec<u8>,
+    ) -> Result<Self, LengthMismatch> {
+        LengthMismatch::check(seq.len(), qual.len())?;
+        Ok(Self {
+            id: id.into(),
+            seq,
+            qual,
+        })
+    }
+
+    /// The record's header line, without the leading `@`.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The record's sequence.
+    pub fn seq(&self) -> &PackedDna {
+        &self.seq
+    }
+
+    /// The record's Phred quality scores, one per base.
+    ///
+    /// These are decoded scores, i.e. the quality characters with the
+    /// Sanger offset of 33 already subtracted.
+    pub fn qual(&self) -> &[u8] {
+        &self.qual
+    }
+}
+
+/// An error that can occur when reading FASTQ records.
+#[derive(Debug, thiserror::Error)]
+pub enum FastqError {
+    /// The underlying reader failed.
+    #[error("failed to read FASTQ input")]
+    Io(#[from] io::Error),
+    /// A record's header line did not start with `@`.
+    #[error("line {0}: expected a header starting with '@'")]
+    Header(usize),
+    /// The input ended partway through a record.
+    #[error("line {0}: unexpected end of input inside a record")]
+    Truncated(usize),
+    /// A record's separator line did not start with `+`.
+    #[error("line {0}: expected a separator starting with '+'")]
+    Separator(usize),
+    /// A record's sequence line contained something other than A, C, G or T.
+    #[error("line {line}: invalid sequence")]
+    Sequence {
+        /// The line of the sequence.
+        line: usize,
+        /// The underlying parse failure.
+        #[source]
+        source: ParseDnaError,
+    },
+    /// A record's quality line contained a character outside `!`..=`~`.
+    #[error("line {0}: invalid quality character")]
+    Quality(usize),
+    /// A record's sequence and quality lines have different lengths.
+    #[error("line {line}: sequence has {seq_len} bases but {qual_len} quality scores")]
+    LengthMismatch {
+        /// The line of the quality scores.
+        line: usize,
+        /// The number of bases in the sequence.
+        seq_len: usize,
+        /// The number of quality scores.
+        qual_len: usize,
+    },
+}
+
+/// Reads FASTQ records from `reader`.
+///
+/// Each record is four lines: a `@` header, the sequence, a `+` separator and
+/// the quality scores, which must be as long as the sequence. Blank lines
+/// between records are skipped.
+///
+/// A read failure is reported in place of the record it interrupts. After a
+/// malformed record, lines are skipped up to the next `@` line that is
+/// followed by a valid sequence line, so that iteration resumes at the next
+/// record rather than misreading the rest of the bad one as headers.
+pub fn read_fastq<R: BufRead>(reader: R) -> impl Iterator<Item = Result<FastqRecord, FastqError>> {
+    let mut lines = reader
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line))
+        .peekable();
+    let mut resynced = None;
+    std::iter::from_fn(move || {
+        let (header_no, header) = match resynced.take() {
+            Some(header) => header,
+            None => loop {
+                match lines.next()? {
+                    (_, Ok(line)) if line.trim().is_empty() => continue,
+                    (line_no, Ok(line)) => break (line_no, line),
+                    (_, Err(err)) => return Some(Err(err.into())),
+                }
+            },
+        };
+        let record = read_record(header_no, header, || {
+            lines
+                .next()
+                .map(|(line_no, line)| line.map(|line| (line_no, line)))
+        });
+        if let Err(err) = &record {
+            if !matches!(err, FastqError::Io(_)) {
+                resynced = resync(&mut lines);
+            }
+        }
+        Some(record)
+    })
+}
+
+/// Skips to the next `@` line that is followed by a valid sequence line and
+/// returns it as the next header.
+///
+/// Returns `None` at the end of the input or at a read failure, which is left
+/// in `lines` for the next call to report.
+fn resync<I>(lines: &mut Peekable<I>) -> Option<(usize, String)>
+where
+    I: Iterator<Item = (usize, io::Result<String>)>,
+{
+    while let Some((line_no, Ok(line))) = lines.next_if(|(_, line)| line.is_ok()) {
+        if !line.starts_with('@') {
+            continue;
+        }
+        let followed_by_seq = matches!(
+            lines.peek(),
+            Some((_, Ok(seq))) if PackedDna::from_ascii(seq.trim_end().as_bytes()).is_ok()
+        );
+        if followed_by_seq {
+            return Some((line_no, line));
+        }
+    }
+    None
+}
+
+fn read_record(
+    header_no: usize,
+    header: String,
+    mut next_line: impl FnMut() -> Option<io::Result<(usize, String)>>,
+) -> Result<FastqRecord, FastqError> {
+    let id = header
+        .strip_prefix('@')
+        .ok_or(FastqError::Header(header_no))?
+        .trim_end()
+        .to_owned();
+    let mut next_line = || match next_line() {
+        Some(line) => line.map_err(FastqError::from),
+        None => Err(FastqError::Truncated(header_no)),
+    };
+    let (seq_no, seq) = next_line()?;
+    let seq = PackedDna::from_ascii(seq.trim_end().as_bytes()).map_err(|source| {
+        FastqError::Sequence {
+            line: seq_no,
+            source,
+        }
+    })?;
+    let (sep_no, sep) = next_line()?;
+    if !sep.starts_with('+') {
+        return Err(FastqError::Separator(sep_no));
+    }
+    let (qual_no, qual) = next_line()?;
+    let qual = qual
+        .trim_end()
+        .bytes()
+        .map(|q| match q {
+            b'!'..=b'~' => Ok(q - PHRED_OFFSET),
+            _ => Err(FastqError::Quality(qual_no)),
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    FastqRecord::new(id, seq, qual).map_err(|err| FastqError::LengthMismatch {
+        line: qual_no,
+        seq_len: err.expected(),
+        qual_len: err.found(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_single_record() {
+        let input = "@read1 sample\nACGTA\n+\nII#5!\n";
+        let records: Vec<_> = read_fastq(input.as_bytes()).collect();
+        assert_eq!(records.len(), 1);
+        let record = records[0].as_ref().unwrap();
+        assert_eq!(record.id(), "read1 sample");
+        assert_eq!(record.seq(), &"ACGTA".parse::<PackedDna>().unwrap());
+        assert_eq!(record.qual().len(), record.seq().len());
+        assert_eq!(record.qual(), &[40, 40, 2, 20, 0]);
+    }
+
+    #[test]
+    fn new_requires_a_score_per_base() {
+        let seq: PackedDna = "ACG".parse().unwrap();
+        let record = FastqRecord::new("r", seq.clone(), vec![30, 30, 2]).unwrap();
+        assert_eq!(record.id(), "r");
+        assert_eq!(record.qual(), &[30, 30, 2]);
+        let err = FastqRecord::new("r", seq, vec![30]).unwrap_err();
+        assert_eq!((err.expected(), err.found()), (3, 1));
+    }
+
+    #[test]
+    fn rejects_mismatched_quality() {
+        let mut records = read_fastq("@r\nACGT\n+\nIII\n".as_bytes());
+        assert!(matches!(
+            records.next(),
+            Some(Err(FastqError::LengthMismatch {
+                line: 4,
+                seq_len: 4,
+                qual_len: 3
+            }))
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_record() {
+        let mut records = read_fastq("@r\nACGT\n+\n".as_bytes());
+        assert!(matches!(
+            records.next(),
+            Some(Err(FastqError::Truncated(1)))
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_separator() {
+        let mut records = read_fastq("@r\nACGT\n-\nIIII\n".as_bytes());
+        assert!(matches!(
+            records.next(),
+            Some(Err(FastqError::Separator(3)))
+        ));
+    }
+
+    #[test]
+    fn resumes_at_the_next_record_after_a_bad_one() {
+        let input = "@r1\nACGT\n-\n@III\n@r2\nGGA\n+\nIII\n";
+        let records: Vec<_> = read_fastq(input.as_bytes()).collect();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(records[0], Err(FastqError::Separator(3))));
+        let record = records[1].as_ref().unwrap();
+        assert_eq!(record.id(), "r2");
+        assert_eq!(record.seq(), &"GGA".parse::<PackedDna>().unwrap());
+    }
+
+    #[test]
+    fn resumes_after_a_bad_sequence_line() {
+        let input = "@r1\nACXT\n+\nIIII\n\n@r2\nAC\n+\nII\n";
+        let mut records = read_fastq(input.as_bytes());
+        assert!(matches!(
+            records.next(),
+            Some(Err(FastqError::Sequence { line: 2, .. }))
+        ));
+        assert_eq!(records.next().unwrap().unwrap().id(), "r2");
+        assert!(records.next().is_none());
+    }
+}
@@ -0,0 +1,427 @@
+//! IUPAC nucleotide ambiguity codes.
+
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display},
+    iter::FromIterator,
+    str::FromStr,
+};
+
+use crate::{
+    packed::{PackedDna, PackedSeq, ParseDnaError},
+    Nuc, ParseNucError,
+};
+
+/// The most concrete sequences [`IupacDna::expand`] will produce.
+pub const MAX_EXPANSIONS: usize = 1 << 16;
+
+/// An IUPAC nucleotide code, standing for one or more concrete nucleotides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IupacNuc {
+    /// Adenine
+    A,
+    /// Cytosine
+    C,
+    /// Guanine
+    G,
+    /// Thymine
+    T,
+    /// Purine (A or G)
+    R,
+    /// Pyrimidine (C or T)
+    Y,
+    /// Strong (C or G)
+    S,
+    /// Weak (A or T)
+    W,
+    /// Keto (G or T)
+    K,
+    /// Amino (A or C)
+    M,
+    /// Not A (C, G or T)
+    B,
+    /// Not C (A, G or T)
+    D,
+    /// Not G (A, C or T)
+    H,
+    /// Not T (A, C or G)
+    V,
+    /// Any nucleotide
+    N,
+}
+
+impl IupacNuc {
+    /// Whether `nuc` is one of the nucleotides this code stands for.
+    pub fn matches(self, nuc: Nuc) -> bool {
+        self.mask() & (1 << nuc.to_bits()) != 0
+    }
+
+    /// The nucleotides this code stands for, in A, C, G, T order.
+    pub fn expand(self) -> impl Iterator<Item = Nuc> {
+        Nuc::ALL
+            .iter()
+            .copied()
+            .filter(move |&nuc| self.matches(nuc))
+    }
+
+    /// The number of nucleotides this code stands for.
+    pub fn degeneracy(self) -> usize {
+        self.mask().count_ones() as usize
+    }
+
+    /// The code standing for exactly the nucleotides set in `mask`, or `None`
+    /// if `mask` is zero or has bits above the lowest four.
+    pub(crate) fn from_mask(mask: u8) -> Option<Self> {
+        const CODES: [IupacNuc; 15] = [
+            IupacNuc::A,
+            IupacNuc::C,
+            IupacNuc::M,
+            IupacNuc::G,
+            IupacNuc::R,
+            IupacNuc::S,
+            IupacNuc::V,
+            IupacNuc::T,
+            IupacNuc::W,
+            IupacNuc::Y,
+            IupacNuc::H,
+            IupacNuc::K,
+            IupacNuc::D,
+            IupacNuc::B,
+            IupacNuc::N,
+        ];
+        match mask {
+            1..=15 => Some(CODES[mask as usize - 1]),
+            _ => None,
+        }
+    }
+
+    /// The set of nucleotides this code stands for, with bit `n` set for the
+    /// nucleotide whose 2-bit code is `n`.
+    pub(crate) fn mask(self) -> u8 {
+        match self {
+            Self::A => 0b0001,
+            Self::C => 0b0010,
+            Self::G => 0b0100,
+            Self::T => 0b1000,
+            Self::R => 0b0101,
+            Self::Y => 0b1010,
+            Self::S => 0b0110,
+            Self::W => 0b1001,
+            Self::K => 0b1100,
+            Self::M => 0b0011,
+            Self::B => 0b1110,
+            Self::D => 0b1101,
+            Self::H => 0b1011,
+            Self::V => 0b0111,
+            Self::N => 0b1111,
+        }
+    }
+}
+
+impl From<Nuc> for IupacNuc {
+    fn from(nuc: Nuc) -> Self {
+        match nuc {
+            Nuc::A => Self::A,
+            Nuc::C => Self::C,
+            Nuc::G => Self::G,
+            Nuc::T => Self::T,
+        }
+    }
+}
+
+impl From<IupacNuc> for char {
+    fn from(code: IupacNuc) -> Self {
+        match code {
+            IupacNuc::A => 'A',
+            IupacNuc::C => 'C',
+            IupacNuc::G => 'G',
+            IupacNuc::T => 'T',
+            IupacNuc::R => 'R',
+            IupacNuc::Y => 'Y',
+            IupacNuc::S => 'S',
+            IupacNuc::W => 'W',
+            IupacNuc::K => 'K',
+            IupacNuc::M => 'M',
+            IupacNuc::B => 'B',
+            IupacNuc::D => 'D',
+            IupacNuc::H => 'H',
+            IupacNuc::V => 'V',
+            IupacNuc::N => 'N',
+        }
+    }
+}
+
+impl Display for IupacNuc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", char::from(*self))
+    }
+}
+
+impl TryFrom<char> for IupacNuc {
+    type Error = ParseNucError<char>;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value.to_ascii_uppercase() {
+            'A' => Ok(Self::A),
+            'C' => Ok(Self::C),
+            'G' => Ok(Self::G),
+            'T' => Ok(Self::T),
+            'R' => Ok(Self::R),
+            'Y' => Ok(Self::Y),
+            'S' => Ok(Self::S),
+            'W' => Ok(Self::W),
+            'K' => Ok(Self::K),
+            'M' => Ok(Self::M),
+            'B' => Ok(Self::B),
+            'D' => Ok(Self::D),
+            'H' => Ok(Self::H),
+            'V' => Ok(Self::V),
+            'N' => Ok(Self::N),
+            _ => Err(ParseNucError(value)),
+        }
+    }
+}
+
+/// An error returned when expanding an [`IupacDna`] would produce more than
+/// [`MAX_EXPANSIONS`] sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("expansion would produce more than {} sequences", MAX_EXPANSIONS)]
+pub struct TooManyExpansions;
+
+/// A DNA sequence that may contain IUPAC ambiguity codes, packed two codes to
+/// a byte.
+///
+/// Each code is stored as the 4-bit set of nucleotides it stands for (bit 0
+/// for A through bit 3 for T). The code at index `i` is in the low nibble of
+/// byte `i / 2` when `i` is even and the high nibble when it is odd.
+pub type IupacDna = PackedSeq<4>;
+
+impl IupacDna {
+    /// Appends a code to the end of the sequence.
+    pub fn push(&mut self, code: IupacNuc) {
+        self.push_code(code.mask());
+    }
+
+    /// Returns the code at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn get(&self, idx: usize) -> IupacNuc {
+        IupacNuc::from_mask(self.code(idx)).expect("packed codes are never empty")
+    }
+
+    /// Replaces the code at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn set(&mut self, idx: usize, code: IupacNuc) {
+        self.set_code(idx, code.mask());
+    }
+
+    /// Returns an iterator over the codes in the sequence.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = IupacNuc> + ExactSizeIterator + '_ {
+        (0..self.len()).map(move |idx| self.get(idx))
+    }
+
+    /// Finds runs of at least `min_len` consecutive concrete bases (A, C, G
+    /// or T), returned as half-open `(start, end)` ranges in order.
+    ///
+    /// Any ambiguity code, not just `N`, breaks a run. Empty runs are never
+    /// reported, even when `min_len` is zero.
+    pub fn unambiguous_stretches(&self, min_len: usize) -> Vec<(usize, usize)> {
+        let min_len = min_len.max(1);
+        let mut stretches = Vec::new();
+        let mut start = 0;
+        for (idx, code) in self.iter().chain(std::iter::once(IupacNuc::N)).enumerate() {
+            if code.degeneracy() == 1 {
+                continue;
+            }
+            if idx - start >= min_len {
+                stretches.push((start, idx));
+            }
+            start = idx + 1;
+        }
+        stretches
+    }
+
+    /// Enumerates every concrete sequence consistent with the ambiguity
+    /// codes, in lexicographic order.
+    ///
+    /// The number of results is the product of each code's degeneracy, which
+    /// grows quickly (a run of nine `N`s alone gives 262,144), so this errors
+    /// rather than produce more than [`MAX_EXPANSIONS`] sequences.
+    pub fn expand(&self) -> Result<Vec<PackedDna>, TooManyExpansions> {
+        let mut total: usize = 1;
+        for code in self.iter() {
+            total = total
+                .checked_mul(code.degeneracy())
+                .filter(|&total| total <= MAX_EXPANSIONS)
+                .ok_or(TooManyExpansions)?;
+        }
+        let mut expansions = vec![PackedDna::with_capacity(self.len())];
+        for code in self.iter() {
+            expansions = expansions
+                .iter()
+                .flat_map(|prefix| {
+                    code.expand().map(move |nuc| {
+                        let mut seq = prefix.clone();
+                        seq.push(nuc);
+                        seq
+                    })
+                })
+                .collect();
+        }
+        Ok(expansions)
+    }
+}
+
+impl FromStr for IupacDna {
+    type Err = ParseDnaError;
+
+    /// Parses a sequence of IUPAC codes, case insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars()
+            .enumerate()
+            .map(|(index, character)| {
+                IupacNuc::try_from(character).map_err(|_| ParseDnaError::new(index, character))
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<IupacNuc> for IupacDna {
+    fn from_iter<I: IntoIterator<Item = IupacNuc>>(iter: I) -> Self {
+        let mut dna = Self::new();
+        for code in iter {
+            dna.push(code);
+        }
+        dna
+    }
+}
+
+impl From<&PackedDna> for IupacDna {
+    fn from(dna: &PackedDna) -> Self {
+        dna.iter().map(IupacNuc::from).collect()
+    }
+}
+
+impl Display for IupacDna {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for code in self.iter() {
+            write!(f, "{}", code)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches() {
+        assert!(IupacNuc::R.matches(Nuc::A));
+        assert!(IupacNuc::R.matches(Nuc::G));
+        assert!(!IupacNuc::R.matches(Nuc::C));
+        assert!(IupacNuc::N.matches(Nuc::T));
+        assert!(!IupacNuc::V.matches(Nuc::T));
+    }
+
+    #[test]
+    fn concrete_codes_match_themselves() {
+        for &nuc in Nuc::ALL.iter() {
+            assert_eq!(IupacNuc::from(nuc).expand().collect::<Vec<_>>(), vec![nuc]);
+        }
+    }
+
+    #[test]
+    fn from_mask_round_trips() {
+        for mask in 1..16 {
+            assert_eq!(IupacNuc::from_mask(mask).unwrap().mask(), mask);
+        }
+        assert_eq!(IupacNuc::from_mask(0), None);
+        assert_eq!(IupacNuc::from_mask(16), None);
+    }
+
+    #[test]
+    fn iupac_dna_round_trip() {
+        let dna: IupacDna = "acgtRYSWKMBDHVN".parse().unwrap();
+        assert_eq!(dna.len(), 15);
+        assert_eq!(dna.get(4), IupacNuc::R);
+        assert_eq!(dna.to_string(), "ACGTRYSWKMBDHVN");
+        assert_eq!("AXN".parse::<IupacDna>().unwrap_err().index(), 1);
+    }
+
+    #[test]
+    fn four_bit_packed_seq_holds_iupac_codes() {
+        let codes = [
+            IupacNuc::R,
+            IupacNuc::A,
+            IupacNuc::N,
+            IupacNuc::Y,
+            IupacNuc::T,
+        ];
+        let mut seq = PackedSeq::<4>::new();
+        for &code in codes.iter() {
+            seq.push(code);
+        }
+        assert_eq!(seq.byte_len(), 3);
+        for (idx, &code) in codes.iter().enumerate() {
+            assert_eq!(seq.get(idx), code);
+        }
+        assert_eq!(seq, "RANYT".parse::<IupacDna>().unwrap());
+        seq.set(2, IupacNuc::G);
+        assert_eq!(seq.to_string(), "RAGYT");
+        assert_eq!(IupacDna::default(), IupacDna::new());
+    }
+
+    #[test]
+    fn unambiguous_stretches() {
+        let dna: IupacDna = "ACGTANNNGGCATCNAC".parse().unwrap();
+        assert_eq!(dna.unambiguous_stretches(4), vec![(0, 5), (8, 14)]);
+        assert_eq!(
+            dna.unambiguous_stretches(2),
+            vec![(0, 5), (8, 14), (15, 17)]
+        );
+        let dna: IupacDna = "NACRGT".parse().unwrap();
+        assert_eq!(dna.unambiguous_stretches(0), vec![(1, 3), (4, 6)]);
+        assert!(IupacDna::new().unambiguous_stretches(1).is_empty());
+    }
+
+    #[test]
+    fn expand() {
+        let dna: IupacDna = "AN".parse().unwrap();
+        let expanded: Vec<String> = dna
+            .expand()
+            .unwrap()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(expanded, vec!["AA", "AC", "AG", "AT"]);
+        assert_eq!(IupacDna::new().expand(), Ok(vec![PackedDna::new()]));
+    }
+
+    #[test]
+    fn expand_is_capped() {
+        let dna: IupacDna = "NNNNNNNNN".parse().unwrap();
+        assert_eq!(dna.expand(), Err(TooManyExpansions));
+        let dna: IupacDna = "NNNNNNNN".parse().unwrap();
+        assert_eq!(dna.expand().unwrap().len(), MAX_EXPANSIONS);
+    }
+
+    #[test]
+    fn tryfrom_char() {
+        assert_eq!(IupacNuc::try_from('y').unwrap(), IupacNuc::Y);
+        assert_eq!(IupacNuc::try_from('N').unwrap(), IupacNuc::N);
+        assert!(IupacNuc::try_from('X').is_err());
+        assert_eq!(
+            IupacNuc::try_from('b')
+                .unwrap()
+                .expand()
+                .collect::<Vec<_>>(),
+            vec![Nuc::C, Nuc::G, Nuc::T]
+        );
+    }
+}
@@ -4,19 +4,20 @@
 
 use std::{convert::TryFrom, fmt::Display, str::FromStr};
 
-// TODO: add a packed module with the PackedDna struct
-//
-// this struct must have the following:
-// 1. A representation that is more memory efficient that simply storing a vector of `Nuc`
-// 2. A FromStr implementation (should be case insensitive like the `Nuc` impl)
-// 3. A `FromIterator` implementation to construct it from an iterator over `Nuc`s
-// 4. A `fn get(&self, idx: usize) -> Nuc` getter for a particular nucleotide
-//
-// Make sure to unit test and document all elements
-// Also, the internal representation of the PackedDna struct should be privately scoped
+pub mod fasta;
+pub mod fastq;
+pub mod iupac;
+pub mod msa;
+pub mod packed;
+pub mod protein;
+pub mod rna;
+pub mod sketch;
+pub mod stats;
 
 /// A nucleotide
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+///
+/// Nucleotides order alphabetically: A < C < G < T.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Nuc {
     /// Adenine
     A,
@@ -33,6 +34,53 @@ pub enum Nuc {
 #[error("failed to parse nucleotide from {0}")]
 pub struct ParseNucError<T: Display>(T);
 
+impl Nuc {
+    /// All four nucleotides, in the order of their 2-bit encoding.
+    pub const ALL: [Nuc; 4] = [Nuc::A, Nuc::C, Nuc::G, Nuc::T];
+
+    /// Returns the Watson-Crick complement of this nucleotide.
+    pub fn complement(self) -> Self {
+        Self::from_bits(self.to_bits() ^ 0b11)
+    }
+
+    /// The 2-bit code used when packing this nucleotide.
+    pub(crate) fn to_bits(self) -> u8 {
+        match self {
+            Self::A => 0b00,
+            Self::C => 0b01,
+            Self::G => 0b10,
+            Self::T => 0b11,
+        }
+    }
+
+    /// Decodes the low two bits of `bits` into a nucleotide.
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => Self::A,
+            0b01 => Self::C,
+            0b10 => Self::G,
+            _ => Self::T,
+        }
+    }
+}
+
+impl From<Nuc> for char {
+    fn from(nuc: Nuc) -> Self {
+        match nuc {
+            Nuc::A => 'A',
+            Nuc::C => 'C',
+            Nuc::G => 'G',
+            Nuc::T => 'T',
+        }
+    }
+}
+
+impl Display for Nuc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", char::from(*self))
+    }
+}
+
 impl TryFrom<char> for Nuc {
     type Error = ParseNucError<char>;
 
@@ -47,6 +95,21 @@ impl TryFrom<char> for Nuc {
     }
 }
 
+impl TryFrom<u8> for Nuc {
+    type Error = ParseNucError<u8>;
+
+    /// Interprets a single ASCII byte as a nucleotide, case insensitively.
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value.to_ascii_uppercase() {
+            b'A' => Ok(Self::A),
+            b'C' => Ok(Self::C),
+            b'G' => Ok(Self::G),
+            b'T' => Ok(Self::T),
+            _ => Err(ParseNucError(value)),
+        }
+    }
+}
+
 impl FromStr for Nuc {
     type Err = ParseNucError<String>;
 
@@ -62,56 +125,8 @@ impl FromStr for Nuc {
     }
 }
 
-struct PackedDna {
-    DNA: Vec<u8>,
-}
-
-impl FromStr for PackedDna {
-    type Err = ParseNucError<String>;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let upper = s.to_ascii_uppercase();
-        let v: Vec<u8> = Vec::new();
-        for c in upper.chars() {
-            match c {
-                'A' => v.push(0),
-                'C' => v.push(1),
-                'G' => v.push(2),
-                'T' => v.push(3),
-                _ => Err(ParseNucError(upper)),
-            }
-        }
-        Ok(v)
-    }
-}
-
-impl PackedDna {
-    fn from_iterator(&self, iter: Vec<Nuc>) -> () {
-        let v: Vec<u8> = Vec::new();
-        for c in iter {
-            match c {
-                Nuc::A => v.push(0),
-                Nuc::C => v.push(1),
-                Nuc::G => v.push(2),
-                Nuc::T => v.push(3),
-            }
-        }
-        self.DNA = v
-    }
-}
-
-impl PackedDna {
-    fn get(&self, idx: usize) -> Nuc {
-        match self.DNA[idx] {
-            0 => Nuc::A,
-            1 => Nuc::C,
-            2 => Nuc::G,
-            3 => Nuc::T, 
-        }
-    }
-}
-
 #[cfg(test)]
+#[allow(non_snake_case)]
 mod tests {
     // TODO: fill in tests
     use super::*;
@@ -152,6 +167,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn tryfrom_byte() {
+        assert_eq!(Nuc::try_from(b'a').unwrap(), Nuc::A);
+        assert_eq!(Nuc::try_from(b'G').unwrap(), Nuc::G);
+        assert!(Nuc::try_from(b'x').is_err());
+    }
+
     #[test]
     fn fromstr_a() {
         let nucFromStrA = Nuc::from_str("a");
@@ -187,4 +209,12 @@ mod tests {
             Err(e) => println!(" {e:?} error is returned"),
         }
     }
+
+    #[test]
+    fn complement() {
+        assert_eq!(Nuc::A.complement(), Nuc::T);
+        assert_eq!(Nuc::C.complement(), Nuc::G);
+        assert_eq!(Nuc::G.complement(), Nuc::C);
+        assert_eq!(Nuc::T.complement(), Nuc::A);
+    }
 }
@@ -0,0 +1,167 @@
+//! Summaries of multiple equal-length (already aligned) sequences.
+
+use crate::{
+    packed::{LengthMismatch, PackedDna},
+    Nuc,
+};
+
+/// Builds the majority-rule consensus of equal-length sequences.
+///
+/// Each position takes the most common base in that column, ties going to
+/// the earliest of A, C, G, T. An empty set gives an empty consensus.
+pub fn consensus(seqs: &[PackedDna]) -> Result<PackedDna, LengthMismatch> {
+    Ok(position_frequency_matrix(seqs)?
+        .iter()
+        .map(|counts| Nuc::ALL[majority(counts)])
+        .collect())
+}
+
+/// Builds the consensus of an alignment with gaps.
+///
+/// Row `r` of `alignment` lays out `seqs[r]`: each entry is either the index
+/// of the base of `seqs[r]` placed in that column, or `None` for a gap. Rows
+/// shorter than the longest are treated as ending in gaps. Columns where
+/// gaps outnumber bases are dropped, since a `PackedDna` cannot hold a gap;
+/// every other column takes its most common base as in [`consensus`].
+///
+/// # Panics
+///
+/// Panics if `alignment` and `seqs` have different numbers of rows, or if an
+/// index is out of bounds for its sequence.
+pub fn consensus_gapped(seqs: &[PackedDna], alignment: &[Vec<Option<usize>>]) -> PackedDna {
+    assert_eq!(
+        seqs.len(),
+        alignment.len(),
+        "alignment must have one row per sequence"
+    );
+    let width = alignment.iter().map(Vec::len).max().unwrap_or(0);
+    (0..width)
+        .filter_map(|column| {
+            let mut counts = [0; 4];
+            let mut bases = 0;
+            for (seq, row) in seqs.iter().zip(alignment) {
+                if let Some(&Some(idx)) = row.get(column) {
+                    counts[seq.get(idx).to_bits() as usize] += 1;
+                    bases += 1;
+                }
+            }
+            let gaps = seqs.len() - bases;
+            if bases == 0 || gaps > bases {
+                None
+            } else {
+                Some(Nuc::ALL[majority(&counts)])
+            }
+        })
+        .collect()
+}
+
+/// Counts each base in every column of equal-length sequences, in A, C, G,
+/// T order. This is the basis of sequence logos and position weight
+/// matrices.
+pub fn position_frequency_matrix(seqs: &[PackedDna]) -> Result<Vec<[usize; 4]>, LengthMismatch> {
+    let len = seqs.first().map_or(0, PackedDna::len);
+    let mut columns = vec![[0; 4]; len];
+    for seq in seqs {
+        LengthMismatch::check(len, seq.len())?;
+        for (column, nuc) in columns.iter_mut().zip(seq.iter()) {
+            column[nuc.to_bits() as usize] += 1;
+        }
+    }
+    Ok(columns)
+}
+
+/// Scores how conserved each column of equal-length sequences is: the
+/// fraction of rows holding the column's most common base, so 1.0 means
+/// every row agrees. An empty set gives no columns.
+pub fn column_conservation(seqs: &[PackedDna]) -> Result<Vec<f64>, LengthMismatch> {
+    Ok(position_frequency_matrix(seqs)?
+        .iter()
+        .map(|counts| counts[majority(counts)] as f64 / seqs.len() as f64)
+        .collect())
+}
+
+/// The index of the largest count, the lowest index winning ties.
+fn majority(counts: &[usize; 4]) -> usize {
+    (0..counts.len()).fold(0, |best, idx| {
+        if counts[idx] > counts[best] {
+            idx
+        } else {
+            best
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dna(s: &str) -> PackedDna {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn consensus_majority() {
+        let seqs = [dna("ACGT"), dna("ACGA"), dna("TCGT")];
+        assert_eq!(consensus(&seqs), Ok(dna("ACGT")));
+    }
+
+    #[test]
+    fn consensus_ties_prefer_earlier_base() {
+        let seqs = [dna("TG"), dna("CA")];
+        assert_eq!(consensus(&seqs), Ok(dna("CA")));
+        assert_eq!(consensus(&[]), Ok(dna("")));
+    }
+
+    #[test]
+    fn consensus_gapped_drops_gap_columns() {
+        // AC-GT
+        // ACTGT
+        // AC-GA
+        let seqs = [dna("ACGT"), dna("ACTGT"), dna("ACGA")];
+        let alignment = vec![
+            vec![Some(0), Some(1), None, Some(2), Some(3)],
+            vec![Some(0), Some(1), Some(2), Some(3), Some(4)],
+            vec![Some(0), Some(1), None, Some(2), Some(3)],
+        ];
+        assert_eq!(consensus_gapped(&seqs, &alignment), dna("ACGT"));
+    }
+
+    #[test]
+    fn consensus_gapped_keeps_minority_gaps() {
+        let seqs = [dna("AT"), dna("ACT"), dna("AGT"), dna("T")];
+        let alignment = vec![
+            vec![Some(0), None, Some(1)],
+            vec![Some(0), Some(1), Some(2)],
+            vec![Some(0), Some(1)],
+            vec![None, None, Some(0)],
+        ];
+        // column 1 has two gaps and two bases, and C wins the tie with G
+        assert_eq!(consensus_gapped(&seqs, &alignment), dna("ACT"));
+        assert_eq!(consensus_gapped(&[], &[]), dna(""));
+    }
+
+    #[test]
+    fn position_frequency_matrix_counts_columns() {
+        let pfm = position_frequency_matrix(&[dna("ACG"), dna("AGG")]).unwrap();
+        assert_eq!(pfm, vec![[2, 0, 0, 0], [0, 1, 1, 0], [0, 0, 2, 0]]);
+        assert!(position_frequency_matrix(&[dna("A"), dna("")]).is_err());
+    }
+
+    #[test]
+    fn column_conservation_scores_columns() {
+        let seqs = [dna("ACGT"), dna("AGGA"), dna("ATGC")];
+        let scores = column_conservation(&seqs).unwrap();
+        assert_eq!(scores[0], 1.0);
+        assert_eq!(scores[2], 1.0);
+        assert_eq!(scores[1], 1.0 / 3.0);
+        assert_eq!(column_conservation(&seqs[..2]).unwrap()[3], 0.5);
+        assert_eq!(column_conservation(&[]), Ok(vec![]));
+        assert!(column_conservation(&[dna("AC"), dna("A")]).is_err());
+    }
+
+    #[test]
+    fn consensus_length_mismatch() {
+        let err = consensus(&[dna("ACGT"), dna("ACG")]).unwrap_err();
+        assert_eq!((err.expected(), err.found()), (4, 3));
+    }
+}
@@ -0,0 +1,1696 @@
+//! A compact, 2-bit-per-base representation of DNA.
+
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    convert::TryFrom,
+    fmt::{self, Display},
+    iter::FromIterator,
+    str::FromStr,
+};
+
+use crate::Nuc;
+
+mod align;
+mod codon;
+mod composition;
+mod gapped;
+mod kmer;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "rand")]
+mod random;
+mod repeat;
+mod search;
+mod softmask;
+mod twobit;
+
+pub use align::EditOp;
+pub use gapped::GappedDna;
+pub use kmer::revcomp_u64;
+pub use search::MIN_ADAPTER_OVERLAP;
+pub use softmask::SoftMaskedDna;
+
+/// The number of nucleotides stored in each byte of the packed buffer.
+const BASES_PER_BYTE: usize = 4;
+
+/// How many bases [`PackedDna::from_bytes_with_progress`] parses between
+/// progress reports. A multiple of [`BASES_PER_BYTE`], so every chunk packs
+/// into whole bytes.
+const PROGRESS_INTERVAL: usize = 1_000_000;
+
+/// The starting state of [`PackedDna::fingerprint`]. Changing it changes
+/// every fingerprint.
+const FINGERPRINT_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Marks bytes in [`ASCII_TO_BITS`] that are not a valid nucleotide.
+const INVALID: u8 = 0xff;
+
+/// Maps every ASCII byte to its 2-bit nucleotide code, or [`INVALID`].
+static ASCII_TO_BITS: [u8; 256] = ascii_table();
+
+const fn ascii_table() -> [u8; 256] {
+    let mut table = [INVALID; 256];
+    table[b'A' as usize] = 0b00;
+    table[b'a' as usize] = 0b00;
+    table[b'C' as usize] = 0b01;
+    table[b'c' as usize] = 0b01;
+    table[b'G' as usize] = 0b10;
+    table[b'g' as usize] = 0b10;
+    table[b'T' as usize] = 0b11;
+    table[b't' as usize] = 0b11;
+    table
+}
+
+/// Whether every character of `s` is a nucleotide (A, C, G or T, in either
+/// case), checked without building a [`PackedDna`]. The empty string is valid.
+pub fn is_valid_dna(s: &str) -> bool {
+    s.bytes()
+        .all(|byte| ASCII_TO_BITS[byte as usize] != INVALID)
+}
+
+/// Decodes one byte of a packed buffer into its four nucleotides, in
+/// sequence order.
+///
+/// A byte holds four 2-bit codes (`A = 00`, `C = 01`, `G = 10`, `T = 11`),
+/// the first nucleotide in the two least significant bits and the last in
+/// the two most significant, so `0b11_10_01_00` decodes to `[A, C, G, T]`.
+pub fn decode_byte(packed: u8) -> [Nuc; 4] {
+    [
+        Nuc::from_bits(packed),
+        Nuc::from_bits(packed >> 2),
+        Nuc::from_bits(packed >> 4),
+        Nuc::from_bits(packed >> 6),
+    ]
+}
+
+/// Complements all four nucleotides of a packed byte at once.
+///
+/// With the 2-bit encoding described in [`decode_byte`], each base's
+/// complement is its bitwise inverse, so this is just `!packed`. It is the
+/// primitive behind [`PackedDna::complement_in_place`].
+pub fn complement_byte(packed: u8) -> u8 {
+    !packed
+}
+
+/// Removes duplicate reads, treating a read and its reverse complement as
+/// the same molecule.
+///
+/// Each distinct read is replaced by its [canonical](PackedDna::to_canonical)
+/// form, kept in order of first appearance.
+pub fn dedup_canonical(reads: Vec<PackedDna>) -> Vec<PackedDna> {
+    let mut seen = HashSet::with_capacity(reads.len());
+    let mut unique = Vec::new();
+    for read in reads {
+        let canonical = read.to_canonical();
+        if !seen.contains(&canonical) {
+            seen.insert(canonical.clone());
+            unique.push(canonical);
+        }
+    }
+    unique
+}
+
+/// An error that can occur when parsing a [`PackedDna`] sequence.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("failed to parse nucleotide from {character:?} at index {index}")]
+pub struct ParseDnaError {
+    index: usize,
+    character: char,
+}
+
+impl ParseDnaError {
+    pub(crate) fn new(index: usize, character: char) -> Self {
+        Self { index, character }
+    }
+
+    /// The index of the offending character in the input.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The offending character.
+    pub fn character(&self) -> char {
+        self.character
+    }
+}
+
+/// An error returned when an index is out of bounds for a [`PackedDna`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("index {index} out of bounds for sequence of length {len}")]
+pub struct IndexError {
+    index: usize,
+    len: usize,
+}
+
+impl IndexError {
+    /// The index that was out of bounds.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The length of the sequence at the time of the access.
+    pub fn seq_len(&self) -> usize {
+        self.len
+    }
+}
+
+/// An error returned when two inputs that must have equal lengths do not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("length mismatch: expected {expected}, found {found}")]
+pub struct LengthMismatch {
+    expected: usize,
+    found: usize,
+}
+
+impl LengthMismatch {
+    /// The length that was required.
+    pub fn expected(&self) -> usize {
+        self.expected
+    }
+
+    /// The length that was provided.
+    pub fn found(&self) -> usize {
+        self.found
+    }
+
+    /// Errors unless `found` equals `expected`.
+    pub(crate) fn check(expected: usize, found: usize) -> Result<(), Self> {
+        if found == expected {
+            Ok(())
+        } else {
+            Err(Self { expected, found })
+        }
+    }
+}
+
+/// A sequence of `BITS`-wide codes packed back to back into bytes.
+///
+/// Code `i` occupies bits `i * BITS` up to `(i + 1) * BITS` of the buffer,
+/// counting from the least significant bit of the first byte, so when `BITS`
+/// does not divide 8 (say, a 3-bit RNA-plus-gap alphabet) some codes straddle
+/// two bytes. Any unused bits in the final byte are always zero, which is
+/// what lets equality and hashing compare the buffers directly. `BITS` must
+/// be between 1 and 8.
+///
+/// The methods here read raw codes; alphabets give them meaning through
+/// aliases with their own typed API, such as [`PackedDna`] (2 bits),
+/// [`GappedRna`](crate::rna::GappedRna) (3 bits) and
+/// [`IupacDna`](crate::iupac::IupacDna) (4 bits). Raw codes can only be
+/// written from within this crate, so each alias only ever holds codes of
+/// its own alphabet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PackedSeq<const BITS: usize> {
+    data: Vec<u8>,
+    len: usize,
+}
+
+/// A DNA sequence packed four nucleotides to a byte.
+///
+/// Each nucleotide is stored as a 2-bit code (`A = 00`, `C = 01`, `G = 10`,
+/// `T = 11`). The base at index `i` lives in byte `i / 4`, at bit offset
+/// `2 * (i % 4)` counting from the least significant bit. Any unused bits in
+/// the final byte are always zero.
+pub type PackedDna = PackedSeq<2>;
+
+impl<const BITS: usize> PackedSeq<BITS> {
+    /// Fails to compile for code widths that cannot be packed.
+    const VALID_WIDTH: () = assert!(BITS != 0 && BITS <= 8, "BITS must be between 1 and 8");
+
+    /// The low `BITS` bits.
+    const CODE_MASK: u8 = ((1u16 << BITS) - 1) as u8;
+
+    /// Creates an empty sequence.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty sequence with room for at least `capacity` codes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let () = Self::VALID_WIDTH;
+        Self {
+            data: Vec::with_capacity((capacity * BITS + 7) / 8),
+            len: 0,
+        }
+    }
+
+    /// The number of codes (for [`PackedDna`], nucleotides) in the sequence.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the sequence is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of bytes used by the packed buffer.
+    pub fn byte_len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Appends a raw code; only its low `BITS` bits are kept.
+    pub(crate) fn push_code(&mut self, code: u8) {
+        let () = Self::VALID_WIDTH;
+        let bit = self.len * BITS;
+        self.data.resize((bit + BITS + 7) / 8, 0);
+        let shifted = u16::from(code & Self::CODE_MASK) << (bit % 8);
+        self.data[bit / 8] |= shifted as u8;
+        if shifted > 0xff {
+            self.data[bit / 8 + 1] |= (shifted >> 8) as u8;
+        }
+        self.len += 1;
+    }
+
+    /// Returns the raw code at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn code(&self, idx: usize) -> u8 {
+        self.assert_in_bounds(idx);
+        let bit = idx * BITS;
+        let low = u16::from(self.data[bit / 8]);
+        let high = self
+            .data
+            .get(bit / 8 + 1)
+            .map_or(0, |&byte| u16::from(byte));
+        ((low | high << 8) >> (bit % 8)) as u8 & Self::CODE_MASK
+    }
+
+    /// Overwrites the code at `idx` with the low `BITS` bits of `code`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub(crate) fn set_code(&mut self, idx: usize, code: u8) {
+        self.assert_in_bounds(idx);
+        let bit = idx * BITS;
+        let clear = u16::from(Self::CODE_MASK) << (bit % 8);
+        let shifted = u16::from(code & Self::CODE_MASK) << (bit % 8);
+        let byte = &mut self.data[bit / 8];
+        *byte = (*byte & !(clear as u8)) | shifted as u8;
+        if clear > 0xff {
+            let byte = &mut self.data[bit / 8 + 1];
+            *byte = (*byte & !((clear >> 8) as u8)) | (shifted >> 8) as u8;
+        }
+    }
+
+    /// Returns an iterator over the raw codes in the sequence.
+    pub fn codes(&self) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_ {
+        (0..self.len).map(move |idx| self.code(idx))
+    }
+
+    fn assert_in_bounds(&self, idx: usize) {
+        assert!(
+            idx < self.len,
+            "index {} out of bounds for sequence of length {}",
+            idx,
+            self.len
+        );
+    }
+}
+
+impl<const BITS: usize> Default for PackedSeq<BITS> {
+    /// Creates an empty sequence, like [`PackedSeq::new`].
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PackedDna {
+    /// Parses a sequence from ASCII bytes, case insensitively.
+    ///
+    /// This is the fast path behind [`FromStr`]: bytes are decoded through a
+    /// lookup table and packed four at a time.
+    pub fn from_ascii(bytes: &[u8]) -> Result<Self, ParseDnaError> {
+        let mut data = Vec::with_capacity(byte_len(bytes.len()));
+        for (chunk_idx, chunk) in bytes.chunks(BASES_PER_BYTE).enumerate() {
+            let mut packed = 0;
+            let mut seen = 0;
+            for (offset, &byte) in chunk.iter().enumerate() {
+                let bits = ASCII_TO_BITS[byte as usize];
+                seen |= bits;
+                packed |= bits << (2 * offset);
+            }
+            if seen == INVALID {
+                let offset = chunk
+                    .iter()
+                    .position(|&byte| ASCII_TO_BITS[byte as usize] == INVALID)
+                    .unwrap_or_default();
+                return Err(ParseDnaError {
+                    index: chunk_idx * BASES_PER_BYTE + offset,
+                    character: char::from(chunk[offset]),
+                });
+            }
+            data.push(packed);
+        }
+        Ok(Self {
+            data,
+            len: bytes.len(),
+        })
+    }
+
+    /// Parses a sequence leniently, replacing every character that is not a
+    /// nucleotide with `fallback` instead of failing.
+    ///
+    /// Returns the sequence along with how many characters were replaced.
+    pub fn from_str_with_stats(s: &str, fallback: Nuc) -> (PackedDna, usize) {
+        let mut substituted = 0;
+        let seq = s
+            .chars()
+            .map(|character| {
+                Nuc::try_from(character).unwrap_or_else(|_| {
+                    substituted += 1;
+                    fallback
+                })
+            })
+            .collect();
+        (seq, substituted)
+    }
+
+    /// Parses a sequence from ASCII bytes like [`from_ascii`](Self::from_ascii),
+    /// calling `progress` with the number of bases parsed so far after every
+    /// million bases and once more at the end of a shorter final chunk.
+    ///
+    /// Nothing is reported for empty input, and reporting stops at the first
+    /// invalid byte.
+    pub fn from_bytes_with_progress<F: FnMut(usize)>(
+        bytes: &[u8],
+        mut progress: F,
+    ) -> Result<PackedDna, ParseDnaError> {
+        let mut data = Vec::with_capacity(byte_len(bytes.len()));
+        let mut done = 0;
+        for chunk in bytes.chunks(PROGRESS_INTERVAL) {
+            let part = Self::from_ascii(chunk)
+                .map_err(|err| ParseDnaError::new(done + err.index, err.character))?;
+            data.extend_from_slice(&part.data);
+            done += chunk.len();
+            progress(done);
+        }
+        Ok(Self {
+            data,
+            len: bytes.len(),
+        })
+    }
+
+    /// Yields the raw packed buffer in slices of `bytes_per_chunk` bytes, the
+    /// last possibly shorter, for code that works on packed data directly.
+    ///
+    /// Each byte holds four bases in the layout described in [`decode_byte`]:
+    /// base `i` is in byte `i / 4` at bits `2 * (i % 4)` and up, coded
+    /// `A = 00`, `C = 01`, `G = 10`, `T = 11`. If the length is not a
+    /// multiple of four, the unused high bits of the final byte are zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_chunk` is zero.
+    pub fn packed_chunks(&self, bytes_per_chunk: usize) -> impl Iterator<Item = &[u8]> {
+        assert!(bytes_per_chunk > 0, "bytes_per_chunk must be non-zero");
+        self.data.chunks(bytes_per_chunk)
+    }
+
+    /// A 64-bit hash of the bases, stable across runs, platforms and versions
+    /// of this crate, unlike [`Hash`], for use as a checksum or a persisted
+    /// deduplication key.
+    ///
+    /// The packed buffer is hashed 32 bases at a time together with the
+    /// length; any bits or bytes past the last base are ignored.
+    pub fn fingerprint(&self) -> u64 {
+        let mut state = kmer::hash_kmer(FINGERPRINT_SEED ^ self.len as u64);
+        for (idx, chunk) in self.data[..byte_len(self.len)].chunks(8).enumerate() {
+            let mut bytes = [0; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            let mut word = u64::from_le_bytes(bytes);
+            let remaining = self.len - 32 * idx;
+            if remaining < 32 {
+                word &= (1 << (2 * remaining)) - 1;
+            }
+            state = kmer::hash_kmer(state ^ word);
+        }
+        state
+    }
+
+    /// Whether the packed buffer is in canonical form: exactly as many bytes
+    /// as `len` requires, with every bit past the last base zeroed.
+    ///
+    /// Equality and hashing compare the raw buffer, so they are only
+    /// meaningful between valid sequences.
+    pub fn validate(&self) -> bool {
+        let used = self.len % BASES_PER_BYTE;
+        let padding_clear = match self.data.last() {
+            Some(&last) if used > 0 => last >> (2 * used) == 0,
+            _ => true,
+        };
+        self.data.len() == byte_len(self.len) && padding_clear
+    }
+
+    /// Restores canonical form by zeroing any bits in the final byte beyond
+    /// `len`, repairing a buffer that fails [`validate`](Self::validate).
+    pub fn normalize(&mut self) {
+        self.data.resize(byte_len(self.len), 0);
+        clear_padding(&mut self.data, self.len);
+    }
+
+    /// Appends a nucleotide to the end of the sequence.
+    pub fn push(&mut self, nuc: Nuc) {
+        let offset = self.len % BASES_PER_BYTE;
+        if offset == 0 {
+            self.data.push(0);
+        }
+        let last = self.data.len() - 1;
+        self.data[last] |= nuc.to_bits() << (2 * offset);
+        self.len += 1;
+    }
+
+    /// Appends all of `other` to the end of the sequence, shifting its packed
+    /// bytes into place rather than copying base by base.
+    pub fn append(&mut self, other: &PackedDna) {
+        let shift = 2 * (self.len % BASES_PER_BYTE);
+        if shift == 0 {
+            self.data.extend_from_slice(&other.data);
+        } else {
+            self.data.reserve(other.data.len());
+            for &byte in &other.data {
+                let last = self.data.len() - 1;
+                self.data[last] |= byte << shift;
+                self.data.push(byte >> (8 - shift));
+            }
+        }
+        self.len += other.len;
+        self.data.truncate(byte_len(self.len));
+    }
+
+    /// Concatenates any number of sequences into one.
+    ///
+    /// The total length is computed up front so the buffer is allocated once,
+    /// which avoids the repeated copying of appending pairwise.
+    pub fn concat_all<I: IntoIterator<Item = PackedDna>>(parts: I) -> PackedDna {
+        let parts: Vec<PackedDna> = parts.into_iter().collect();
+        let total = parts.iter().map(PackedDna::len).sum();
+        let mut joined = PackedDna::with_capacity(total);
+        for part in &parts {
+            joined.append(part);
+        }
+        joined
+    }
+
+    /// Removes the last nucleotide and returns it, or `None` if the sequence
+    /// is empty.
+    pub fn pop(&mut self) -> Option<Nuc> {
+        if self.len == 0 {
+            return None;
+        }
+        let nuc = self.get_unchecked(self.len - 1);
+        self.len -= 1;
+        let offset = self.len % BASES_PER_BYTE;
+        if offset == 0 {
+            self.data.pop();
+        } else {
+            let last = self.data.len() - 1;
+            self.data[last] &= !(0b11 << (2 * offset));
+        }
+        Some(nuc)
+    }
+
+    /// Shortens the sequence to its first `len` nucleotides.
+    ///
+    /// Has no effect if `len` is not less than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        self.data.truncate(byte_len(len));
+        clear_padding(&mut self.data, len);
+        self.len = len;
+    }
+
+    /// Keeps only the nucleotides for which `f` returns `true`, given each
+    /// nucleotide's original index, compacting the sequence in place.
+    pub fn retain<F: FnMut(usize, Nuc) -> bool>(&mut self, mut f: F) {
+        let mut kept = 0;
+        for idx in 0..self.len {
+            let nuc = self.get_unchecked(idx);
+            if f(idx, nuc) {
+                self.set_unchecked(kept, nuc);
+                kept += 1;
+            }
+        }
+        self.truncate(kept);
+    }
+
+    /// Removes the nucleotide at `idx` and returns it, replacing it with the
+    /// last nucleotide of the sequence.
+    ///
+    /// This does not preserve the order of the remaining nucleotides, but runs
+    /// in O(1) rather than shifting everything after `idx`.
+    pub fn swap_remove(&mut self, idx: usize) -> Result<Nuc, IndexError> {
+        self.check_index(idx)?;
+        let last = self.pop().expect("sequence is non-empty");
+        if idx == self.len {
+            return Ok(last);
+        }
+        let removed = self.get_unchecked(idx);
+        self.set_unchecked(idx, last);
+        Ok(removed)
+    }
+
+    /// Applies a batch of substitutions, each replacing the base at an index.
+    ///
+    /// Every index is validated before anything is written, so on error the
+    /// sequence is left unchanged. Later edits to the same index win.
+    pub fn apply_edits(&mut self, edits: &[(usize, Nuc)]) -> Result<(), IndexError> {
+        for &(idx, _) in edits {
+            self.check_index(idx)?;
+        }
+        for &(idx, nuc) in edits {
+            self.set_unchecked(idx, nuc);
+        }
+        Ok(())
+    }
+
+    /// Returns the nucleotide at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn get(&self, idx: usize) -> Nuc {
+        self.assert_in_bounds(idx);
+        self.get_unchecked(idx)
+    }
+
+    /// Returns an iterator over the nucleotides in the sequence.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            dna: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+
+    /// Decodes the sequence into a vector of nucleotides.
+    pub fn to_vec(&self) -> Vec<Nuc> {
+        self.iter().collect()
+    }
+
+    /// Returns an iterator over each nucleotide paired with its index, like
+    /// `slice.iter().enumerate()`.
+    pub fn iter_indexed(&self) -> impl Iterator<Item = (usize, Nuc)> + '_ {
+        self.iter().enumerate()
+    }
+
+    /// Splits the sequence into the prefix `0..idx` and the suffix `idx..len`.
+    ///
+    /// Returns `None` if `idx` is greater than the length of the sequence.
+    pub fn split_at(&self, idx: usize) -> Option<(PackedDna, PackedDna)> {
+        if idx > self.len {
+            return None;
+        }
+        Some((self.extract(0, idx), self.extract(idx, self.len)))
+    }
+
+    /// Returns a copy of the sequence in which every base whose quality is
+    /// below `min_qual` is replaced with `mask_with`.
+    ///
+    /// `quals` holds one Phred score per base (already decoded, not the
+    /// ASCII-offset FASTQ characters). Masking is usually done with `N`, but
+    /// since a `PackedDna` can only hold A, C, G and T the caller chooses which
+    /// of those stands in for an unknown base.
+    pub fn mask_low_quality(
+        &self,
+        quals: &[u8],
+        min_qual: u8,
+        mask_with: Nuc,
+    ) -> Result<PackedDna, LengthMismatch> {
+        self.check_len(quals.len())?;
+        Ok(self
+            .iter()
+            .zip(quals)
+            .map(|(nuc, &qual)| if qual < min_qual { mask_with } else { nuc })
+            .collect())
+    }
+
+    /// Overwrites every base in the half-open range `start..end` with `with`.
+    /// The range is clamped to the length of the sequence.
+    pub fn mask_range(&mut self, start: usize, end: usize, with: Nuc) {
+        self.mask_intervals(&[(start, end)], with);
+    }
+
+    /// Overwrites every base inside any of the half-open `(start, end)`
+    /// intervals with `with`.
+    ///
+    /// Intervals may overlap and come in any order; they are clamped to the
+    /// length of the sequence, and each base is written at most once. Whole
+    /// packed bytes inside an interval are filled at once.
+    pub fn mask_intervals(&mut self, intervals: &[(usize, usize)], with: Nuc) {
+        let mut spans: Vec<(usize, usize)> = intervals
+            .iter()
+            .map(|&(start, end)| (start.min(self.len), end.min(self.len)))
+            .filter(|&(start, end)| start < end)
+            .collect();
+        spans.sort_unstable();
+        let filled = 0b0101_0101 * with.to_bits();
+        let mut next = 0;
+        for (start, end) in spans {
+            let mut idx = start.max(next);
+            while idx < end && idx % BASES_PER_BYTE != 0 {
+                self.set_unchecked(idx, with);
+                idx += 1;
+            }
+            let whole_end = end - end % BASES_PER_BYTE;
+            if idx < whole_end {
+                self.data[idx / BASES_PER_BYTE..whole_end / BASES_PER_BYTE].fill(filled);
+                idx = whole_end;
+            }
+            while idx < end {
+                self.set_unchecked(idx, with);
+                idx += 1;
+            }
+            next = next.max(end);
+        }
+    }
+
+    /// Returns the sequence with all leading and trailing bases that appear
+    /// in `bases` removed, like [`str::trim_matches`].
+    pub fn trim_matches(&self, bases: &[Nuc]) -> PackedDna {
+        let keep = |nuc: Nuc| !bases.contains(&nuc);
+        match self.iter().position(keep) {
+            Some(start) => {
+                let end = self.len - self.iter().rev().position(keep).unwrap_or(0);
+                self.extract(start, end)
+            }
+            None => PackedDna::new(),
+        }
+    }
+
+    /// Returns `parts - 1` increasing split indices that divide the sequence
+    /// into `parts` contiguous segments of nearly equal length, for handing
+    /// out to parallel workers.
+    ///
+    /// Each index is the even split point rounded to the nearest multiple of
+    /// four, so every segment but the last starts and ends on a byte boundary
+    /// of the packed buffer and segments differ from an even share by at most
+    /// a byte's worth of bases. Very short sequences may get empty segments.
+    /// Returns nothing if `parts` is 0 or 1.
+    pub fn balanced_split_points(&self, parts: usize) -> Vec<usize> {
+        (1..parts)
+            .map(|part| {
+                let even = part * self.len / parts;
+                let snapped = (even + BASES_PER_BYTE / 2) / BASES_PER_BYTE * BASES_PER_BYTE;
+                snapped.min(self.len)
+            })
+            .collect()
+    }
+
+    /// Returns the bases from `flank` before `pos` to `flank` after it, the
+    /// local context of a site, clamped to the ends of the sequence. Returns
+    /// `None` if `pos` is out of bounds.
+    pub fn context(&self, pos: usize, flank: usize) -> Option<PackedDna> {
+        if pos >= self.len {
+            return None;
+        }
+        let end = pos.saturating_add(flank).saturating_add(1).min(self.len);
+        Some(self.extract(pos.saturating_sub(flank), end))
+    }
+
+    /// Returns the first `keep` bases, dropping the rest of the 3' end.
+    ///
+    /// `keep` is clamped to the length, so asking for more bases than there
+    /// are returns the whole sequence.
+    pub fn trim_3prime(&self, keep: usize) -> PackedDna {
+        self.extract(0, keep.min(self.len))
+    }
+
+    /// Rotates the sequence in place so the base at index `n` moves to the
+    /// front and the first `n` bases wrap around to the back, as for a
+    /// circular molecule. `n` is taken modulo the length.
+    pub fn rotate_left(&mut self, n: usize) {
+        if self.is_empty() {
+            return;
+        }
+        let n = n % self.len;
+        if n == 0 {
+            return;
+        }
+        let mut rotated = self.extract(n, self.len);
+        rotated.append(&self.extract(0, n));
+        *self = rotated;
+    }
+
+    /// Returns a sequence alternating the bases of `self` and `other`
+    /// (`s0, o0, s1, o1, ...`), which must have equal lengths.
+    pub fn interleave(&self, other: &PackedDna) -> Result<PackedDna, LengthMismatch> {
+        self.check_len(other.len)?;
+        let mut interleaved = PackedDna::with_capacity(2 * self.len);
+        for (a, b) in self.iter().zip(other.iter()) {
+            interleaved.push(a);
+            interleaved.push(b);
+        }
+        Ok(interleaved)
+    }
+
+    /// Replaces every base with its complement, keeping the order.
+    pub fn complement_in_place(&mut self) {
+        for byte in &mut self.data {
+            *byte = complement_byte(*byte);
+        }
+        clear_padding(&mut self.data, self.len);
+    }
+
+    /// Returns the complement of the sequence, keeping the order.
+    ///
+    /// Unlike [`reverse_complement`](Self::reverse_complement), this does not
+    /// reverse the sequence, so the result is the opposite strand read 3' to
+    /// 5'.
+    pub fn complement(&self) -> PackedDna {
+        let mut complement = self.clone();
+        complement.complement_in_place();
+        complement
+    }
+
+    /// Returns the reverse complement of the sequence.
+    pub fn reverse_complement(&self) -> PackedDna {
+        self.reverse_complement_iter().collect()
+    }
+
+    /// Returns an iterator over the reverse complement of the sequence,
+    /// decoding it lazily instead of building a new buffer.
+    pub fn reverse_complement_iter(&self) -> impl Iterator<Item = Nuc> + '_ {
+        self.iter().rev().map(Nuc::complement)
+    }
+
+    /// Returns a copy with only the bases in `start..end` replaced by their
+    /// reverse complement, as for an inversion, or `None` if the range is
+    /// reversed or runs past the end.
+    pub fn reverse_complement_range(&self, start: usize, end: usize) -> Option<PackedDna> {
+        if start > end || end > self.len {
+            return None;
+        }
+        let mut inverted = self.extract(0, start);
+        inverted.append(&self.extract(start, end).reverse_complement());
+        inverted.append(&self.extract(end, self.len));
+        Some(inverted)
+    }
+
+    /// Returns the sequence read from last base to first, without
+    /// complementing.
+    pub fn reverse(&self) -> PackedDna {
+        self.rev_iter().collect()
+    }
+
+    /// Yields the bases from last to first without complementing or
+    /// allocating; shorthand for `self.iter().rev()`.
+    pub fn rev_iter(&self) -> impl Iterator<Item = Nuc> + '_ {
+        self.iter().rev()
+    }
+
+    /// Returns whichever of the sequence and its reverse complement is
+    /// lexicographically smaller, so both strands of a read map to the same
+    /// representative.
+    pub fn to_canonical(&self) -> PackedDna {
+        let reverse_complement = self.reverse_complement();
+        if reverse_complement < *self {
+            reverse_complement
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Whether `self` is the reverse complement of `other`.
+    ///
+    /// Sequences of different lengths are never reverse complements of each
+    /// other.
+    pub fn is_reverse_complement_of(&self, other: &PackedDna) -> bool {
+        self.len == other.len
+            && self
+                .iter()
+                .zip(other.iter().rev())
+                .all(|(a, b)| a == b.complement())
+    }
+
+    /// Reads the nucleotide at `idx` without checking it against `len`.
+    fn get_unchecked(&self, idx: usize) -> Nuc {
+        let byte = self.data[idx / BASES_PER_BYTE];
+        Nuc::from_bits(byte >> (2 * (idx % BASES_PER_BYTE)))
+    }
+
+    /// Copies the bases in `start..end` into a new sequence, realigning the
+    /// packed bits so the copy starts at bit zero.
+    ///
+    /// The range must already be known to lie within the sequence.
+    fn extract(&self, start: usize, end: usize) -> PackedDna {
+        debug_assert!(start <= end && end <= self.len);
+        let len = end - start;
+        let first = start / BASES_PER_BYTE;
+        let shift = 2 * (start % BASES_PER_BYTE);
+        let mut data: Vec<u8> = (0..byte_len(len))
+            .map(|i| {
+                let low = self.data[first + i] >> shift;
+                let high = match self.data.get(first + i + 1) {
+                    Some(&next) if shift > 0 => next << (8 - shift),
+                    _ => 0,
+                };
+                low | high
+            })
+            .collect();
+        clear_padding(&mut data, len);
+        PackedDna { data, len }
+    }
+
+    /// Overwrites the nucleotide at `idx` without checking it against `len`.
+    fn set_unchecked(&mut self, idx: usize, nuc: Nuc) {
+        let shift = 2 * (idx % BASES_PER_BYTE);
+        let byte = &mut self.data[idx / BASES_PER_BYTE];
+        *byte = (*byte & !(0b11 << shift)) | (nuc.to_bits() << shift);
+    }
+
+    fn check_len(&self, found: usize) -> Result<(), LengthMismatch> {
+        LengthMismatch::check(self.len, found)
+    }
+
+    fn check_index(&self, idx: usize) -> Result<(), IndexError> {
+        if idx < self.len {
+            Ok(())
+        } else {
+            Err(IndexError {
+                index: idx,
+                len: self.len,
+            })
+        }
+    }
+}
+
+/// The number of bytes needed to pack `len` nucleotides.
+fn byte_len(len: usize) -> usize {
+    (len + BASES_PER_BYTE - 1) / BASES_PER_BYTE
+}
+
+/// Zeroes the bits of the final byte of `data` that lie beyond `len` bases.
+fn clear_padding(data: &mut [u8], len: usize) {
+    let used = len % BASES_PER_BYTE;
+    if let (Some(last), true) = (data.last_mut(), used > 0) {
+        *last &= (1 << (2 * used)) - 1;
+    }
+}
+
+/// Sequences order lexicographically by base, a proper prefix sorting first.
+impl Ord for PackedDna {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl PartialOrd for PackedDna {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for PackedDna {
+    type Err = ParseDnaError;
+
+    /// Parses a sequence of nucleotides, case insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_ascii(s.as_bytes()).map_err(|err| {
+            // a non-ASCII input makes byte and char indices disagree, so
+            // report the first invalid char rather than the first bad byte
+            s.chars()
+                .enumerate()
+                .find(|&(_, character)| Nuc::try_from(character).is_err())
+                .map_or(err, |(index, character)| ParseDnaError { index, character })
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PackedDna {
+    type Error = ParseDnaError;
+
+    /// Parses a sequence from ASCII bytes; see [`PackedDna::from_ascii`].
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_ascii(bytes)
+    }
+}
+
+impl FromIterator<Nuc> for PackedDna {
+    fn from_iter<I: IntoIterator<Item = Nuc>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut dna = Self::with_capacity(iter.size_hint().0);
+        for nuc in iter {
+            dna.push(nuc);
+        }
+        dna
+    }
+}
+
+impl<const N: usize> From<[Nuc; N]> for PackedDna {
+    fn from(nucs: [Nuc; N]) -> Self {
+        nucs.iter().copied().collect()
+    }
+}
+
+impl From<Vec<Nuc>> for PackedDna {
+    fn from(nucs: Vec<Nuc>) -> Self {
+        nucs.into_iter().collect()
+    }
+}
+
+impl Display for PackedDna {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for nuc in self.iter() {
+            write!(f, "{}", nuc)?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for PackedDna {
+    type Item = Nuc;
+    type IntoIter = IntoIter;
+
+    /// Consumes the sequence, yielding its nucleotides in order.
+    fn into_iter(self) -> Self::IntoIter {
+        let back = self.len;
+        IntoIter {
+            dna: self,
+            front: 0,
+            back,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a PackedDna {
+    type Item = Nuc;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the nucleotides of a [`PackedDna`].
+///
+/// Created by [`PackedDna::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    dna: &'a PackedDna,
+    front: usize,
+    back: usize,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = Nuc;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let nuc = self.dna.get_unchecked(self.front);
+        self.front += 1;
+        Some(nuc)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.dna.get_unchecked(self.back))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// A consuming iterator over the nucleotides of a [`PackedDna`].
+///
+/// Created by the [`IntoIterator`] implementation on [`PackedDna`].
+#[derive(Debug, Clone)]
+pub struct IntoIter {
+    dna: PackedDna,
+    front: usize,
+    back: usize,
+}
+
+impl Iterator for IntoIter {
+    type Item = Nuc;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let nuc = self.dna.get_unchecked(self.front);
+        self.front += 1;
+        Some(nuc)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl DoubleEndedIterator for IntoIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.dna.get_unchecked(self.back))
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dna(s: &str) -> PackedDna {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn fromstr_case_insensitive() {
+        assert_eq!(dna("acgt"), dna("ACGT"));
+        assert_eq!(dna("AcGtA").to_string(), "ACGTA");
+    }
+
+    #[test]
+    fn fromstr_invalid() {
+        let err = PackedDna::from_str("ACXT").unwrap_err();
+        assert_eq!(err.index(), 2);
+        assert_eq!(err.character(), 'X');
+    }
+
+    #[test]
+    fn fromstr_packs_four_per_byte() {
+        assert_eq!(dna("").data.len(), 0);
+        assert_eq!(dna("ACGT").data, vec![0b11_10_01_00]);
+        assert_eq!(dna("ACGTA").data.len(), 2);
+    }
+
+    #[test]
+    fn from_ascii_matches_char_parser() {
+        let inputs = [
+            "",
+            "a",
+            "ACGT",
+            "acgtA",
+            "TTGCAACGTAGC",
+            "ACGN",
+            "AC-GT",
+            "acgtacgx",
+            "AC\u{e9}GT",
+        ];
+        for input in inputs.iter() {
+            let expected: Result<PackedDna, _> = input.chars().map(Nuc::try_from).collect();
+            let actual = PackedDna::from_str(input);
+            assert_eq!(actual.is_ok(), expected.is_ok(), "{}", input);
+            if let (Ok(actual), Ok(expected)) = (actual, expected) {
+                assert_eq!(actual, expected);
+            }
+        }
+        let err = PackedDna::from_str("AC\u{e9}GT").unwrap_err();
+        assert_eq!((err.index(), err.character()), (2, '\u{e9}'));
+        let err = PackedDna::from_ascii(b"ACGTAxGT").unwrap_err();
+        assert_eq!((err.index(), err.character()), (5, 'x'));
+    }
+
+    #[test]
+    fn packed_seq_codes_straddle_bytes() {
+        let codes = [5, 0, 7, 3, 6, 1, 2, 4, 7];
+        let mut seq = PackedSeq::<3>::new();
+        for &code in codes.iter() {
+            seq.push_code(code);
+        }
+        assert_eq!(seq.len(), 9);
+        assert_eq!(seq.byte_len(), 4);
+        assert_eq!(seq.codes().collect::<Vec<_>>(), codes);
+        seq.set_code(2, 0);
+        seq.set_code(5, 6);
+        assert_eq!(seq.code(1), 0);
+        assert_eq!(seq.code(2), 0);
+        assert_eq!(seq.code(5), 6);
+        assert_eq!(seq.code(6), 2);
+        // only the low three bits are stored
+        seq.push_code(0b1111_1001);
+        assert_eq!(seq.code(9), 0b001);
+    }
+
+    #[test]
+    fn packed_seq_matches_packed_dna_layout() {
+        let seq = dna("ACGTTGCAA");
+        let codes: Vec<u8> = seq.iter().map(Nuc::to_bits).collect();
+        assert_eq!(seq.codes().collect::<Vec<_>>(), codes);
+        let mut raw = PackedDna::new();
+        for &code in codes.iter() {
+            raw.push_code(code);
+        }
+        assert_eq!(raw, seq);
+        raw.set_code(8, Nuc::T.to_bits());
+        assert_eq!(raw, dna("ACGTTGCAT"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn packed_seq_code_out_of_bounds() {
+        PackedSeq::<3>::new().code(0);
+    }
+
+    #[test]
+    fn from_str_with_stats() {
+        assert_eq!(
+            PackedDna::from_str_with_stats("ACXGYT", Nuc::A),
+            (dna("ACAGAT"), 2)
+        );
+        assert_eq!(
+            PackedDna::from_str_with_stats("acgt", Nuc::C),
+            (dna("ACGT"), 0)
+        );
+        assert_eq!(
+            PackedDna::from_str_with_stats("N\u{e9}-", Nuc::T),
+            (dna("TTT"), 3)
+        );
+    }
+
+    #[test]
+    fn try_from_bytes() {
+        assert_eq!(PackedDna::try_from(&b"acgT"[..]), Ok(dna("ACGT")));
+        let err = PackedDna::try_from(&b"ACN"[..]).unwrap_err();
+        assert_eq!((err.index(), err.character()), (2, 'N'));
+    }
+
+    #[test]
+    fn from_bytes_with_progress() {
+        let bytes = b"GATTACA".repeat(500_000);
+        let mut reports = Vec::new();
+        let seq = PackedDna::from_bytes_with_progress(&bytes, |done| reports.push(done)).unwrap();
+        assert_eq!(reports, vec![1_000_000, 2_000_000, 3_000_000, 3_500_000]);
+        assert_eq!(Ok(seq), PackedDna::try_from(&bytes[..]));
+
+        let mut calls = 0;
+        let empty = PackedDna::from_bytes_with_progress(b"", |_| calls += 1).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn from_bytes_with_progress_offsets_errors() {
+        let mut bytes = vec![b'A'; 1_500_000];
+        bytes[1_234_567] = b'x';
+        let mut reports = Vec::new();
+        let err =
+            PackedDna::from_bytes_with_progress(&bytes, |done| reports.push(done)).unwrap_err();
+        assert_eq!((err.index(), err.character()), (1_234_567, 'x'));
+        assert_eq!(reports, vec![1_000_000]);
+    }
+
+    #[test]
+    fn is_valid_dna() {
+        assert!(super::is_valid_dna("acgt"));
+        assert!(super::is_valid_dna(""));
+        assert!(!super::is_valid_dna("acgx"));
+        assert!(!super::is_valid_dna("AC\u{e9}GT"));
+    }
+
+    #[test]
+    fn packed_chunks() {
+        let seq: PackedDna = "ACGT".repeat(9).parse().unwrap();
+        let chunks: Vec<&[u8]> = seq.packed_chunks(4).collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].len(), 1);
+        assert_eq!(chunks.concat(), seq.data);
+        assert_eq!(chunks[0][0], 0b11_10_01_00);
+        assert_eq!(dna("").packed_chunks(8).count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn packed_chunks_rejects_zero() {
+        dna("ACGT").packed_chunks(0).count();
+    }
+
+    #[test]
+    fn decode_byte() {
+        assert_eq!(
+            super::decode_byte(0b11100100),
+            [Nuc::A, Nuc::C, Nuc::G, Nuc::T]
+        );
+        assert_eq!(super::decode_byte(0), [Nuc::A; 4]);
+        let seq = dna("GATTACAC");
+        let decoded: Vec<Nuc> = seq
+            .data
+            .iter()
+            .flat_map(|&b| super::decode_byte(b))
+            .collect();
+        assert_eq!(decoded, seq.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn complement_byte() {
+        for byte in 0..=u8::MAX {
+            let mut expected = super::decode_byte(byte);
+            for nuc in expected.iter_mut() {
+                *nuc = nuc.complement();
+            }
+            assert_eq!(super::decode_byte(super::complement_byte(byte)), expected);
+        }
+    }
+
+    #[test]
+    fn from_iter() {
+        let dna: PackedDna = vec![Nuc::G, Nuc::A, Nuc::T].into_iter().collect();
+        assert_eq!(dna.len(), 3);
+        assert_eq!(dna.to_string(), "GAT");
+    }
+
+    #[test]
+    fn from_array_and_vec() {
+        assert_eq!(PackedDna::from([Nuc::A, Nuc::C]), dna("AC"));
+        assert_eq!(PackedDna::from([]), dna(""));
+        let nucs = vec![Nuc::G, Nuc::A, Nuc::T, Nuc::T, Nuc::A];
+        assert_eq!(PackedDna::from(nucs), dna("GATTA"));
+    }
+
+    #[test]
+    fn get() {
+        let dna = dna("ACGTTGCA");
+        let expected = [
+            Nuc::A,
+            Nuc::C,
+            Nuc::G,
+            Nuc::T,
+            Nuc::T,
+            Nuc::G,
+            Nuc::C,
+            Nuc::A,
+        ];
+        for (idx, nuc) in expected.iter().enumerate() {
+            assert_eq!(dna.get(idx), *nuc);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_out_of_bounds() {
+        dna("ACG").get(3);
+    }
+
+    #[test]
+    fn iter_both_ends() {
+        let dna = dna("ACGTA");
+        assert_eq!(dna.iter().len(), 5);
+        assert_eq!(dna.iter().rev().collect::<PackedDna>().to_string(), "ATGCA");
+    }
+
+    #[test]
+    fn iter_indexed() {
+        let pairs: Vec<_> = dna("AC").iter_indexed().collect();
+        assert_eq!(pairs, vec![(0, Nuc::A), (1, Nuc::C)]);
+    }
+
+    #[test]
+    fn into_iter_preserves_order() {
+        let original = dna("GATTACA");
+        let collected: Vec<Nuc> = original.clone().into_iter().collect();
+        assert_eq!(collected, original.iter().collect::<Vec<_>>());
+        assert_eq!(collected.into_iter().collect::<PackedDna>(), original);
+
+        let mut iter = dna("ACG").into_iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(Nuc::G));
+        assert_eq!(iter.next(), Some(Nuc::A));
+        assert_eq!(iter.len(), 1);
+    }
+
+    #[test]
+    fn pop() {
+        let mut dna = dna("ACGTA");
+        assert_eq!(dna.pop(), Some(Nuc::A));
+        assert_eq!(dna, self::dna("ACGT"));
+        assert_eq!(dna.data.len(), 1);
+        assert_eq!(dna.pop(), Some(Nuc::T));
+        assert_eq!(dna, self::dna("ACG"));
+        assert_eq!(PackedDna::new().pop(), None);
+    }
+
+    #[test]
+    fn append_realigns() {
+        for split in 0..=9 {
+            let text = "GATTACACG";
+            let mut seq = dna(&text[..split]);
+            seq.append(&dna(&text[split..]));
+            assert_eq!(seq, dna(text), "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn concat_all() {
+        let parts = vec![dna("AC"), dna("G"), dna("TACG")];
+        assert_eq!(PackedDna::concat_all(parts), dna("ACGTACG"));
+        assert_eq!(PackedDna::concat_all(Vec::new()), dna(""));
+    }
+
+    #[test]
+    fn normalize_clears_dirty_padding() {
+        let clean = dna("ACGTAC");
+        let mut dirty = clean.clone();
+        dirty.data[1] |= 0b1111_0000;
+        assert!(!dirty.validate());
+        assert_ne!(dirty, clean);
+        dirty.normalize();
+        assert!(dirty.validate());
+        assert_eq!(dirty, clean);
+    }
+
+    #[test]
+    fn fingerprint() {
+        let parsed = dna("ACGTTGCAAGCTTAGGCATCGATCGGATTACAG");
+        let built: PackedDna = parsed.iter().collect();
+        assert_eq!(parsed.fingerprint(), built.fingerprint());
+        let mut edited = parsed.clone();
+        edited.apply_edits(&[(20, Nuc::T)]).unwrap();
+        assert_ne!(edited.fingerprint(), parsed.fingerprint());
+        // trailing A bases look like zeroed padding, so the length matters
+        assert_ne!(dna("AC").fingerprint(), dna("ACA").fingerprint());
+        assert_ne!(dna("").fingerprint(), dna("A").fingerprint());
+        // pinned so that persisted fingerprints stay valid
+        assert_eq!(dna("ACGT").fingerprint(), 1_583_243_673_011_216_429);
+    }
+
+    #[test]
+    fn fingerprint_ignores_dirty_padding() {
+        let clean = dna("ACGTAC");
+        let mut dirty = clean.clone();
+        dirty.data[1] |= 0b1111_0000;
+        assert_eq!(dirty.fingerprint(), clean.fingerprint());
+        dirty.data.extend_from_slice(&[0xff; 9]);
+        assert_eq!(dirty.fingerprint(), clean.fingerprint());
+    }
+
+    #[test]
+    fn truncate() {
+        let mut dna = dna("ACGTACG");
+        dna.truncate(10);
+        assert_eq!(dna.len(), 7);
+        dna.truncate(5);
+        assert_eq!(dna, self::dna("ACGTA"));
+        dna.truncate(0);
+        assert_eq!(dna, PackedDna::new());
+    }
+
+    #[test]
+    fn retain() {
+        let mut dna = dna("ACAGA");
+        assert_eq!(dna.byte_len(), 2);
+        dna.retain(|_, nuc| nuc != Nuc::A);
+        assert_eq!(dna, self::dna("CG"));
+        assert_eq!(dna.byte_len(), 1);
+    }
+
+    #[test]
+    fn retain_by_index() {
+        let mut dna = dna("ACGTACGT");
+        dna.retain(|idx, _| idx % 2 == 1);
+        assert_eq!(dna, self::dna("CTCT"));
+    }
+
+    #[test]
+    fn swap_remove() {
+        let mut dna = dna("ACGT");
+        assert_eq!(dna.swap_remove(0), Ok(Nuc::A));
+        assert_eq!(dna, self::dna("TCG"));
+        assert_eq!(dna.swap_remove(2), Ok(Nuc::G));
+        assert_eq!(dna, self::dna("TC"));
+    }
+
+    #[test]
+    fn swap_remove_out_of_bounds() {
+        let mut dna = dna("ACGT");
+        let err = dna.swap_remove(4).unwrap_err();
+        assert_eq!((err.index(), err.seq_len()), (4, 4));
+        assert_eq!(dna, self::dna("ACGT"));
+    }
+
+    #[test]
+    fn split_at() {
+        let whole = dna("ACGTAC");
+        assert_eq!(whole.split_at(3), Some((dna("ACG"), dna("TAC"))));
+        assert_eq!(whole.split_at(0), Some((dna(""), whole.clone())));
+        assert_eq!(whole.split_at(6), Some((whole.clone(), dna(""))));
+        assert_eq!(whole.split_at(7), None);
+    }
+
+    #[test]
+    fn split_at_every_offset() {
+        let whole = dna("GATTACATTGCAGCA");
+        let text = whole.to_string();
+        for idx in 0..=whole.len() {
+            let (prefix, suffix) = whole.split_at(idx).unwrap();
+            assert_eq!(prefix, dna(&text[..idx]));
+            assert_eq!(suffix, dna(&text[idx..]));
+        }
+    }
+
+    #[test]
+    fn mask_low_quality() {
+        let dna = dna("ACGTAC");
+        let quals = [30, 12, 40, 19, 20, 2];
+        assert_eq!(
+            dna.mask_low_quality(&quals, 20, Nuc::A),
+            Ok(self::dna("AAGAAA"))
+        );
+        let err = dna.mask_low_quality(&quals[..5], 20, Nuc::A).unwrap_err();
+        assert_eq!((err.expected(), err.found()), (6, 5));
+    }
+
+    #[test]
+    fn mask_intervals() {
+        let mut seq = dna("ACGTACGT");
+        seq.mask_intervals(&[(0, 2), (5, 7)], Nuc::A);
+        assert_eq!(seq, dna("AAGTAAAT"));
+
+        let mut seq = dna("ACGTACGTACGTA");
+        seq.mask_intervals(&[(10, 40), (1, 3), (2, 9), (6, 6)], Nuc::T);
+        assert_eq!(seq, dna("ATTTTTTTTCTTT"));
+        assert!(seq.validate());
+
+        let mut seq = dna("ACGTACGT");
+        seq.mask_range(3, 100, Nuc::C);
+        assert_eq!(seq, dna("ACGCCCCC"));
+    }
+
+    #[test]
+    fn mask_intervals_matches_per_base() {
+        let original = dna("ACGTTGCAACGTTGCAAC");
+        for start in 0..=original.len() {
+            for end in start..=original.len() + 2 {
+                let mut fast = original.clone();
+                fast.mask_intervals(&[(start, end)], Nuc::G);
+                let slow: PackedDna = original
+                    .iter_indexed()
+                    .map(|(idx, nuc)| {
+                        if (start..end).contains(&idx) {
+                            Nuc::G
+                        } else {
+                            nuc
+                        }
+                    })
+                    .collect();
+                assert_eq!(fast, slow, "{}..{}", start, end);
+            }
+        }
+    }
+
+    #[test]
+    fn trim_matches() {
+        assert_eq!(dna("AACGTAA").trim_matches(&[Nuc::A]), dna("CGT"));
+        assert_eq!(dna("TACGTAT").trim_matches(&[Nuc::A, Nuc::T]), dna("CG"));
+        assert_eq!(dna("AAAA").trim_matches(&[Nuc::A]), dna(""));
+        assert_eq!(dna("CGC").trim_matches(&[]), dna("CGC"));
+    }
+
+    #[test]
+    fn balanced_split_points() {
+        let seq: PackedDna = "ACGT".repeat(25).parse().unwrap();
+        let points = seq.balanced_split_points(4);
+        assert_eq!(points, vec![24, 52, 76]);
+        let mut bounds = vec![0];
+        bounds.extend(&points);
+        bounds.push(seq.len());
+        for segment in bounds.windows(2) {
+            let size = segment[1] - segment[0];
+            assert!((21..=29).contains(&size), "{:?}", segment);
+        }
+        assert!(points.iter().all(|&point| point % 4 == 0));
+
+        assert!(seq.balanced_split_points(1).is_empty());
+        assert!(seq.balanced_split_points(0).is_empty());
+        assert_eq!(dna("ACG").balanced_split_points(3), vec![0, 3]);
+    }
+
+    #[test]
+    fn context() {
+        let seq = dna("ACGTACGTTG");
+        assert_eq!(seq.context(4, 2), Some(dna("GTACG")));
+        assert_eq!(seq.context(1, 2), Some(dna("ACGT")));
+        assert_eq!(seq.context(9, 3), Some(dna("GTTG")));
+        assert_eq!(seq.context(0, 0), Some(dna("A")));
+        assert_eq!(seq.context(3, usize::MAX), Some(seq.clone()));
+        assert_eq!(seq.context(10, 2), None);
+    }
+
+    #[test]
+    fn trim_3prime() {
+        let seq = dna("ACGTACGT");
+        assert_eq!(seq.trim_3prime(5), dna("ACGTA"));
+        assert_eq!(seq.trim_3prime(5).byte_len(), 2);
+        assert_eq!(seq.trim_3prime(20), seq);
+        assert_eq!(seq.trim_3prime(0), dna(""));
+    }
+
+    #[test]
+    fn rotate_left() {
+        let mut seq = dna("ACGT");
+        seq.rotate_left(1);
+        assert_eq!(seq, dna("CGTA"));
+        seq.rotate_left(4);
+        assert_eq!(seq, dna("CGTA"));
+        seq.rotate_left(7);
+        assert_eq!(seq, dna("ACGT"));
+        let mut empty = PackedDna::new();
+        empty.rotate_left(3);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn interleave() {
+        assert_eq!(dna("AAAA").interleave(&dna("TTTT")), Ok(dna("ATATATAT")));
+        assert_eq!(dna("").interleave(&dna("")), Ok(dna("")));
+        let err = dna("AAAA").interleave(&dna("TTT")).unwrap_err();
+        assert_eq!((err.expected(), err.found()), (4, 3));
+    }
+
+    #[test]
+    fn complement() {
+        let original = dna("ACGT");
+        assert_eq!(original.complement(), dna("TGCA"));
+        assert_eq!(original, dna("ACGT"));
+        assert_eq!(dna("AAAAA").complement(), dna("TTTTT"));
+    }
+
+    #[test]
+    fn complement_in_place() {
+        let mut seq = dna("ACGTAC");
+        seq.complement_in_place();
+        assert_eq!(seq, dna("TGCATG"));
+    }
+
+    #[test]
+    fn apply_edits() {
+        let mut seq = dna("ACGT");
+        assert_eq!(seq.apply_edits(&[(0, Nuc::T), (3, Nuc::A)]), Ok(()));
+        assert_eq!(seq, dna("TCGA"));
+    }
+
+    #[test]
+    fn apply_edits_is_atomic() {
+        let mut seq = dna("ACGT");
+        let err = seq.apply_edits(&[(1, Nuc::A), (4, Nuc::A)]).unwrap_err();
+        assert_eq!(err.index(), 4);
+        assert_eq!(seq, dna("ACGT"));
+    }
+
+    #[test]
+    fn reverse_complement() {
+        assert_eq!(dna("AACG").reverse_complement(), dna("CGTT"));
+        assert_eq!(dna("").reverse_complement(), dna(""));
+    }
+
+    #[test]
+    fn ordering_is_lexicographic() {
+        assert!(dna("ACGT") < dna("ACTA"));
+        assert!(dna("AC") < dna("ACA"));
+        assert!(dna("TA") > dna("GTTT"));
+        assert_eq!(dna("CAT").cmp(&dna("CAT")), Ordering::Equal);
+    }
+
+    #[test]
+    fn to_vec() {
+        assert_eq!(dna("GAT").to_vec(), vec![Nuc::G, Nuc::A, Nuc::T]);
+        assert!(dna("").to_vec().is_empty());
+    }
+
+    #[test]
+    fn reverse_complement_iter() {
+        let seq = dna("AACGTTGCA");
+        let lazy: Vec<Nuc> = seq.reverse_complement_iter().collect();
+        assert_eq!(lazy, seq.reverse_complement().to_vec());
+    }
+
+    #[test]
+    fn reverse_complement_range() {
+        let seq = dna("AAACGTAA");
+        // ACGT is its own reverse complement
+        assert_eq!(seq.reverse_complement_range(2, 6), Some(seq.clone()));
+        let inverted = seq.reverse_complement_range(1, 5).unwrap();
+        assert_eq!(inverted, dna("ACGTTTAA"));
+        assert_eq!(inverted.extract(0, 1), seq.extract(0, 1));
+        assert_eq!(inverted.extract(5, 8), seq.extract(5, 8));
+        assert_eq!(seq.reverse_complement_range(3, 3), Some(seq.clone()));
+        assert_eq!(
+            seq.reverse_complement_range(0, 8),
+            Some(seq.reverse_complement())
+        );
+        assert_eq!(seq.reverse_complement_range(5, 2), None);
+        assert_eq!(seq.reverse_complement_range(2, 9), None);
+    }
+
+    #[test]
+    fn rev_iter() {
+        let seq = dna("AACGTTGCA");
+        let reversed: Vec<Nuc> = seq.rev_iter().collect();
+        assert_eq!(reversed, seq.reverse().to_vec());
+        assert_eq!(seq.reverse(), dna("ACGTTGCAA"));
+        assert_eq!(dna("").rev_iter().next(), None);
+    }
+
+    #[test]
+    fn to_canonical() {
+        let read = dna("TTGCA");
+        let canonical = read.to_canonical();
+        assert_eq!(canonical, dna("TGCAA"));
+        assert_eq!(read.reverse_complement().to_canonical(), canonical);
+        assert_eq!(dna("ACGT").to_canonical(), dna("ACGT"));
+    }
+
+    #[test]
+    fn dedup_canonical() {
+        let read = dna("TTGCA");
+        let reads = vec![
+            read.clone(),
+            dna("ACGTA"),
+            read.clone(),
+            read.reverse_complement(),
+        ];
+        assert_eq!(
+            super::dedup_canonical(reads),
+            vec![dna("TGCAA"), dna("ACGTA")]
+        );
+        assert!(super::dedup_canonical(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn is_reverse_complement_of() {
+        assert!(dna("ACGT").is_reverse_complement_of(&dna("ACGT")));
+        assert!(dna("AAAA").is_reverse_complement_of(&dna("TTTT")));
+        assert!(!dna("AAAA").is_reverse_complement_of(&dna("AAAA")));
+        assert!(!dna("AAAA").is_reverse_complement_of(&dna("TTT")));
+    }
+}
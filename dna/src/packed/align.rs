@@ -0,0 +1,456 @@
+//! Pairwise alignment of [`PackedDna`] sequences.
+
+use super::{LengthMismatch, PackedDna};
+use crate::Nuc;
+
+/// One step of an [`edit_script`](PackedDna::edit_script), applied while
+/// walking the source sequence from left to right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditOp {
+    /// Keep the current source base.
+    Match,
+    /// Replace the current source base with the given base.
+    Substitute(Nuc),
+    /// Insert the given base before the current source base.
+    Insert(Nuc),
+    /// Drop the current source base.
+    Delete,
+}
+
+impl PackedDna {
+    /// Counts the positions at which two equal-length sequences differ.
+    ///
+    /// Whole packed bytes are compared at once: XORing them leaves a non-zero
+    /// 2-bit group exactly where the bases differ.
+    pub fn hamming_distance(&self, other: &PackedDna) -> Result<usize, LengthMismatch> {
+        self.check_len(other.len)?;
+        Ok(self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(a, b)| {
+                let diff = a ^ b;
+                ((diff | (diff >> 1)) & 0b0101_0101).count_ones() as usize
+            })
+            .sum())
+    }
+
+    /// Returns the percentage of positions at which two equal-length
+    /// (already aligned) sequences agree.
+    ///
+    /// Two empty sequences are considered identical, giving `100.0`.
+    pub fn percent_identity(&self, other: &PackedDna) -> Result<f64, LengthMismatch> {
+        let mismatches = self.hamming_distance(other)?;
+        if self.is_empty() {
+            return Ok(100.0);
+        }
+        Ok(100.0 * (self.len - mismatches) as f64 / self.len as f64)
+    }
+
+    /// Flags each position of two equal-length sequences, `true` where they
+    /// differ.
+    pub fn diff_mask(&self, other: &PackedDna) -> Result<Vec<bool>, LengthMismatch> {
+        self.check_len(other.len)?;
+        Ok(self.iter().zip(other.iter()).map(|(a, b)| a != b).collect())
+    }
+
+    /// Lists every position at which two equal-length sequences differ, with
+    /// the base from `self` followed by the base from `other`.
+    pub fn diff(&self, other: &PackedDna) -> Result<Vec<(usize, Nuc, Nuc)>, LengthMismatch> {
+        self.check_len(other.len)?;
+        Ok(self
+            .iter()
+            .zip(other.iter())
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(idx, (a, b))| (idx, a, b))
+            .collect())
+    }
+
+    /// Counts the substitutions between two equal-length sequences, split into
+    /// `(transitions, transversions)`.
+    ///
+    /// Transitions are the purine (A↔G) and pyrimidine (C↔T) swaps; every
+    /// other substitution is a transversion.
+    pub fn ts_tv(&self, other: &PackedDna) -> Result<(usize, usize), LengthMismatch> {
+        self.check_len(other.len)?;
+        let mut counts = (0, 0);
+        for (a, b) in self.iter().zip(other.iter()) {
+            // in the 2-bit encoding, transitions differ in the high bit only
+            match a.to_bits() ^ b.to_bits() {
+                0b00 => {}
+                0b10 => counts.0 += 1,
+                _ => counts.1 += 1,
+            }
+        }
+        Ok(counts)
+    }
+
+    /// The Levenshtein distance between `self` and `other`: the fewest
+    /// substitutions, insertions and deletions turning one into the other.
+    pub fn edit_distance(&self, other: &PackedDna) -> usize {
+        let others: Vec<Nuc> = other.iter().collect();
+        let mut prev: Vec<usize> = (0..=others.len()).collect();
+        let mut curr = vec![0; prev.len()];
+        for (i, a) in self.iter().enumerate() {
+            curr[0] = i + 1;
+            for (j, &b) in others.iter().enumerate() {
+                let substitute = prev[j] + usize::from(a != b);
+                curr[j + 1] = substitute.min(prev[j + 1] + 1).min(curr[j] + 1);
+            }
+            std::mem::swap(&mut prev, &mut curr);
+        }
+        prev[others.len()]
+    }
+
+    /// Returns a shortest sequence of edits transforming `self` into `other`,
+    /// found by tracing back through the full edit-distance matrix.
+    ///
+    /// The number of non-[`Match`](EditOp::Match) operations equals the
+    /// [`edit_distance`](Self::edit_distance). Where several scripts are
+    /// equally short, substitutions are preferred over deletions, and
+    /// deletions over insertions.
+    pub fn edit_script(&self, other: &PackedDna) -> Vec<EditOp> {
+        let ours: Vec<Nuc> = self.iter().collect();
+        let others: Vec<Nuc> = other.iter().collect();
+        let (n, m) = (ours.len(), others.len());
+        let width = m + 1;
+        let mut dist = vec![0; (n + 1) * width];
+        for i in 0..=n {
+            for j in 0..=m {
+                dist[i * width + j] = match (i, j) {
+                    (0, _) => j,
+                    (_, 0) => i,
+                    _ => {
+                        let substitute = dist[(i - 1) * width + j - 1]
+                            + usize::from(ours[i - 1] != others[j - 1]);
+                        let delete = dist[(i - 1) * width + j] + 1;
+                        let insert = dist[i * width + j - 1] + 1;
+                        substitute.min(delete).min(insert)
+                    }
+                };
+            }
+        }
+
+        let mut ops = Vec::with_capacity(n.max(m));
+        let (mut i, mut j) = (n, m);
+        while i > 0 || j > 0 {
+            let here = dist[i * width + j];
+            if i > 0 && j > 0 {
+                let cost = usize::from(ours[i - 1] != others[j - 1]);
+                if here == dist[(i - 1) * width + j - 1] + cost {
+                    ops.push(if cost == 0 {
+                        EditOp::Match
+                    } else {
+                        EditOp::Substitute(others[j - 1])
+                    });
+                    i -= 1;
+                    j -= 1;
+                    continue;
+                }
+            }
+            if i > 0 && here == dist[(i - 1) * width + j] + 1 {
+                ops.push(EditOp::Delete);
+                i -= 1;
+            } else {
+                ops.push(EditOp::Insert(others[j - 1]));
+                j -= 1;
+            }
+        }
+        ops.reverse();
+        ops
+    }
+
+    /// The [`edit_distance`](Self::edit_distance) between `self` and
+    /// `other` if it is at most `max`, or `None` otherwise.
+    ///
+    /// Only the diagonal band of width `2 * max + 1` is filled in (Ukkonen's
+    /// cut-off), and the search stops as soon as a whole row exceeds `max`,
+    /// so this costs O(`max` * len) rather than O(len²).
+    pub fn edit_distance_within(&self, other: &PackedDna, max: usize) -> Option<usize> {
+        let ours: Vec<Nuc> = self.iter().collect();
+        let others: Vec<Nuc> = other.iter().collect();
+        let (n, m) = (ours.len(), others.len());
+        if n.max(m) - n.min(m) > max {
+            return None;
+        }
+        // anything past `max` is as good as infinite
+        let over = max + 1;
+        let mut prev: Vec<usize> = (0..=m).map(|j| j.min(over)).collect();
+        let mut curr = vec![over; m + 1];
+        for i in 1..=n {
+            let lo = i.saturating_sub(max);
+            let hi = (i + max).min(m);
+            if lo == 0 {
+                curr[0] = i;
+            } else {
+                curr[lo - 1] = over;
+            }
+            let mut row_min = if lo == 0 { i } else { over };
+            for j in lo.max(1)..=hi {
+                let substitute = prev[j - 1] + usize::from(ours[i - 1] != others[j - 1]);
+                let cell = substitute.min(prev[j] + 1).min(curr[j - 1] + 1).min(over);
+                curr[j] = cell;
+                row_min = row_min.min(cell);
+            }
+            if hi < m {
+                // the next row reads one cell further right than this band
+                curr[hi + 1] = over;
+            }
+            if row_min > max {
+                return None;
+            }
+            std::mem::swap(&mut prev, &mut curr);
+        }
+        Some(prev[m]).filter(|&distance| distance <= max)
+    }
+
+    /// Returns the optimal global alignment score of `self` against `other`,
+    /// computed with Needleman-Wunsch and a linear gap cost.
+    ///
+    /// Each aligned pair adds `match_score` or `mismatch`, and each gapped
+    /// position adds `gap`, so penalties should be passed as negative values.
+    pub fn global_align(
+        &self,
+        other: &PackedDna,
+        match_score: i32,
+        mismatch: i32,
+        gap: i32,
+    ) -> i32 {
+        let others: Vec<Nuc> = other.iter().collect();
+        let mut prev: Vec<i32> = (0..=others.len() as i32).map(|j| j * gap).collect();
+        let mut curr = vec![0; prev.len()];
+        for (i, a) in self.iter().enumerate() {
+            curr[0] = (i as i32 + 1) * gap;
+            for (j, &b) in others.iter().enumerate() {
+                let diagonal = prev[j] + if a == b { match_score } else { mismatch };
+                curr[j + 1] = diagonal.max(prev[j + 1] + gap).max(curr[j] + gap);
+            }
+            std::mem::swap(&mut prev, &mut curr);
+        }
+        prev[others.len()]
+    }
+
+    /// The length of the longest suffix of `self` that equals a prefix of
+    /// `other`, as when overlapping reads during assembly, or `None` if no
+    /// such overlap is at least `min_overlap` bases. An empty overlap is never
+    /// reported.
+    pub fn overlap_len(&self, other: &PackedDna, min_overlap: usize) -> Option<usize> {
+        (min_overlap.max(1)..=self.len.min(other.len))
+            .rev()
+            .find(|&len| {
+                let start = self.len - len;
+                (0..len).all(|i| self.get_unchecked(start + i) == other.get_unchecked(i))
+            })
+    }
+
+    /// Returns the longest contiguous sequence that occurs in both `self` and
+    /// `other`, or an empty sequence if they share no bases.
+    ///
+    /// When several substrings are equally long, the one ending earliest in
+    /// `self` is returned.
+    pub fn longest_common_substring(&self, other: &PackedDna) -> PackedDna {
+        let others: Vec<Nuc> = other.iter().collect();
+        let mut prev = vec![0; others.len() + 1];
+        let mut curr = vec![0; others.len() + 1];
+        let (mut best_len, mut best_end) = (0, 0);
+        for (i, a) in self.iter().enumerate() {
+            for (j, &b) in others.iter().enumerate() {
+                curr[j + 1] = if a == b { prev[j] + 1 } else { 0 };
+                if curr[j + 1] > best_len {
+                    best_len = curr[j + 1];
+                    best_end = i + 1;
+                }
+            }
+            std::mem::swap(&mut prev, &mut curr);
+        }
+        self.extract(best_end - best_len, best_end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dna(s: &str) -> PackedDna {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn hamming_distance() {
+        assert_eq!(dna("ACGTA").hamming_distance(&dna("AGGTC")), Ok(2));
+        assert_eq!(dna("").hamming_distance(&dna("")), Ok(0));
+        assert!(dna("ACGT").hamming_distance(&dna("ACG")).is_err());
+    }
+
+    #[test]
+    fn percent_identity() {
+        assert_eq!(dna("ACGT").percent_identity(&dna("ACGA")), Ok(75.0));
+        assert_eq!(dna("").percent_identity(&dna("")), Ok(100.0));
+        assert_eq!(dna("ACGT").percent_identity(&dna("TGCA")), Ok(0.0));
+        assert!(dna("ACGT").percent_identity(&dna("ACG")).is_err());
+    }
+
+    #[test]
+    fn diff_mask() {
+        assert_eq!(
+            dna("ACGT").diff_mask(&dna("AGGT")),
+            Ok(vec![false, true, false, false])
+        );
+        assert_eq!(dna("").diff_mask(&dna("")), Ok(vec![]));
+        assert!(dna("ACGT").diff_mask(&dna("AC")).is_err());
+    }
+
+    #[test]
+    fn diff() {
+        assert_eq!(
+            dna("ACGT").diff(&dna("AGGT")),
+            Ok(vec![(1, Nuc::C, Nuc::G)])
+        );
+        assert_eq!(dna("ACGT").diff(&dna("ACGT")), Ok(vec![]));
+        let err = dna("ACGT").diff(&dna("ACGTA")).unwrap_err();
+        assert_eq!((err.expected(), err.found()), (4, 5));
+    }
+
+    #[test]
+    fn ts_tv() {
+        assert_eq!(dna("AG").ts_tv(&dna("GA")), Ok((2, 0)));
+        assert_eq!(dna("AC").ts_tv(&dna("TG")), Ok((0, 2)));
+        assert_eq!(dna("ACGT").ts_tv(&dna("ATGA")), Ok((1, 1)));
+        assert!(dna("A").ts_tv(&dna("")).is_err());
+    }
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(dna("ACGT").edit_distance(&dna("ACGT")), 0);
+        assert_eq!(dna("ACGT").edit_distance(&dna("AGT")), 1);
+        assert_eq!(dna("GATTACA").edit_distance(&dna("GCATGCT")), 4);
+        assert_eq!(dna("").edit_distance(&dna("ACG")), 3);
+    }
+
+    fn apply(seq: &PackedDna, script: &[EditOp]) -> PackedDna {
+        let mut source = seq.iter();
+        let mut out = PackedDna::new();
+        for op in script {
+            match *op {
+                EditOp::Match => out.push(source.next().unwrap()),
+                EditOp::Substitute(nuc) => {
+                    source.next().unwrap();
+                    out.push(nuc);
+                }
+                EditOp::Insert(nuc) => out.push(nuc),
+                EditOp::Delete => {
+                    source.next().unwrap();
+                }
+            }
+        }
+        assert_eq!(source.next(), None);
+        out
+    }
+
+    #[test]
+    fn edit_script_insertion_and_substitution() {
+        let (from, to) = (dna("ACGT"), dna("ACCGA"));
+        let script = from.edit_script(&to);
+        assert_eq!(apply(&from, &script), to);
+        assert_eq!(
+            script,
+            vec![
+                EditOp::Match,
+                EditOp::Insert(Nuc::C),
+                EditOp::Match,
+                EditOp::Match,
+                EditOp::Substitute(Nuc::A),
+            ]
+        );
+    }
+
+    #[test]
+    fn edit_script_length_matches_distance() {
+        let seqs = ["", "A", "ACGT", "GATTACA", "GCATGCT", "TTAGG"];
+        for a in seqs.iter() {
+            for b in seqs.iter() {
+                let (a, b) = (dna(a), dna(b));
+                let script = a.edit_script(&b);
+                assert_eq!(apply(&a, &script), b);
+                let edits = script.iter().filter(|op| **op != EditOp::Match).count();
+                assert_eq!(edits, a.edit_distance(&b));
+            }
+        }
+    }
+
+    #[test]
+    fn edit_distance_within_matches_full_dp() {
+        let seqs = [
+            "",
+            "A",
+            "ACGT",
+            "AGT",
+            "GATTACA",
+            "GCATGCT",
+            "TTTTTTTT",
+            "ACGTACGTAC",
+            "CGTACGTACG",
+        ];
+        for a in seqs.iter() {
+            for b in seqs.iter() {
+                let (a, b) = (dna(a), dna(b));
+                let full = a.edit_distance(&b);
+                for max in 0..=10 {
+                    let expected = if full <= max { Some(full) } else { None };
+                    assert_eq!(
+                        a.edit_distance_within(&b, max),
+                        expected,
+                        "{} {} {}",
+                        a,
+                        b,
+                        max
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn global_align_identical() {
+        assert_eq!(dna("ACGT").global_align(&dna("ACGT"), 2, -1, -2), 8);
+    }
+
+    #[test]
+    fn global_align_one_mismatch() {
+        assert_eq!(dna("ACGT").global_align(&dna("ACCT"), 2, -1, -2), 5);
+    }
+
+    #[test]
+    fn overlap_len() {
+        assert_eq!(dna("ACGTAA").overlap_len(&dna("TAACC"), 1), Some(3));
+        assert_eq!(dna("ACGTAA").overlap_len(&dna("TAACC"), 4), None);
+        assert_eq!(dna("ACGTAA").overlap_len(&dna("CCGGT"), 1), None);
+        // the longest overlap wins over a shorter one
+        assert_eq!(dna("GAAA").overlap_len(&dna("AAAT"), 1), Some(3));
+        assert_eq!(dna("ACG").overlap_len(&dna("ACG"), 0), Some(3));
+        assert_eq!(dna("").overlap_len(&dna("ACG"), 0), None);
+    }
+
+    #[test]
+    fn longest_common_substring() {
+        let lcs = dna("ACGTAC").longest_common_substring(&dna("TACGTA"));
+        assert_eq!(lcs, dna("ACGTA"));
+        assert_eq!(dna("AAAA").longest_common_substring(&dna("CGCG")), dna(""));
+        assert_eq!(dna("").longest_common_substring(&dna("ACGT")), dna(""));
+    }
+
+    #[test]
+    fn longest_common_substring_tie_takes_first() {
+        assert_eq!(
+            dna("ACTG").longest_common_substring(&dna("TGAC")),
+            dna("AC")
+        );
+    }
+
+    #[test]
+    fn global_align_gaps() {
+        assert_eq!(dna("ACGT").global_align(&dna("AGT"), 1, -1, -1), 2);
+        assert_eq!(dna("").global_align(&dna("ACG"), 1, -1, -2), -6);
+    }
+}
@@ -0,0 +1,204 @@
+//! Codon-level views of [`PackedDna`].
+
+use super::PackedDna;
+use crate::Nuc;
+
+impl PackedDna {
+    /// Returns codon iterators for reading frames 0, 1 and 2.
+    ///
+    /// Frame `n` starts at base `n` and yields every complete codon after it;
+    /// trailing bases that do not fill a codon are skipped.
+    pub fn frames(&self) -> [impl Iterator<Item = [Nuc; 3]> + '_; 3] {
+        [self.codons(0), self.codons(1), self.codons(2)]
+    }
+
+    /// The fraction of complete frame-0 codons whose third base is G or C, a
+    /// common measure of codon bias, or 0.0 if there is no complete codon.
+    pub fn gc3_content(&self) -> f64 {
+        let codons = self.len / 3;
+        if codons == 0 {
+            return 0.0;
+        }
+        let gc = self
+            .codons(0)
+            .filter(|codon| matches!(codon[2], Nuc::C | Nuc::G))
+            .count();
+        gc as f64 / codons as f64
+    }
+
+    /// Whether the length is a whole number of codons.
+    pub fn is_multiple_of_codon(&self) -> bool {
+        self.len % 3 == 0
+    }
+
+    /// Appends `base` until the length is a whole number of codons, so frame
+    /// 0 has no trailing partial codon.
+    pub fn pad_to_codon(&mut self, base: Nuc) {
+        while !self.is_multiple_of_codon() {
+            self.push(base);
+        }
+    }
+
+    /// Whether reading frame `frame` contains a TAA, TAG or TGA codon.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` is not 0, 1 or 2.
+    pub fn has_stop_codon(&self, frame: usize) -> bool {
+        assert!(frame < 3, "frame must be 0, 1 or 2, got {}", frame);
+        self.codons(frame).any(is_stop)
+    }
+
+    /// Finds open reading frames on both strands: stretches from an ATG start
+    /// codon up to the first in-frame stop codon, at least `min_codons` long.
+    ///
+    /// Returns `(frame, start, codons)` triples. Frames 0 to 2 are read along
+    /// the sequence itself and frames 3 to 5 along its reverse complement,
+    /// with `start` indexing into whichever strand was read. `codons` counts
+    /// the start codon but not the stop codon. Each ORF is reported from its
+    /// first ATG only, and a start with no stop before the end of the strand
+    /// is not reported.
+    pub fn find_orfs(&self, min_codons: usize) -> Vec<(usize, usize, usize)> {
+        let mut orfs = Vec::new();
+        let reverse_complement = self.reverse_complement();
+        for (strand, seq) in [self, &reverse_complement].iter().enumerate() {
+            for frame in 0..3 {
+                let mut open = None;
+                for (idx, codon) in seq.codons(frame).enumerate() {
+                    match open {
+                        None if codon == [Nuc::A, Nuc::T, Nuc::G] => open = Some(idx),
+                        Some(first) if is_stop(codon) => {
+                            if idx - first >= min_codons {
+                                orfs.push((3 * strand + frame, frame + 3 * first, idx - first));
+                            }
+                            open = None;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        orfs
+    }
+
+    /// The length in codons of the longest ORF in any of the six frames, as
+    /// found by [`find_orfs`](Self::find_orfs), or 0 if there is none.
+    pub fn max_orf_length(&self) -> usize {
+        self.find_orfs(1)
+            .iter()
+            .map(|&(_, _, codons)| codons)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Yields the complete codons of the reading frame starting at `frame`.
+    pub(crate) fn codons(&self, frame: usize) -> impl Iterator<Item = [Nuc; 3]> + '_ {
+        (frame..)
+            .step_by(3)
+            .take_while(move |&start| start + 3 <= self.len)
+            .map(move |start| {
+                [
+                    self.get_unchecked(start),
+                    self.get_unchecked(start + 1),
+                    self.get_unchecked(start + 2),
+                ]
+            })
+    }
+}
+
+/// Whether `codon` is one of the standard stop codons.
+pub(crate) fn is_stop(codon: [Nuc; 3]) -> bool {
+    matches!(
+        codon,
+        [Nuc::T, Nuc::A, Nuc::A] | [Nuc::T, Nuc::A, Nuc::G] | [Nuc::T, Nuc::G, Nuc::A]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dna(s: &str) -> PackedDna {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn frames_of_length_8() {
+        let seq = dna("ATGCATGC");
+        let [f0, f1, f2] = seq.frames();
+        assert_eq!(f0.count(), 2);
+        assert_eq!(f1.count(), 2);
+        assert_eq!(f2.count(), 2);
+    }
+
+    #[test]
+    fn gc3_content() {
+        assert_eq!(dna("ATGCGC").gc3_content(), 1.0);
+        assert_eq!(dna("ATGAAAGGC").gc3_content(), 2.0 / 3.0);
+        // the trailing partial codon is ignored
+        assert_eq!(dna("AAACG").gc3_content(), 0.0);
+        assert_eq!(dna("GC").gc3_content(), 0.0);
+    }
+
+    #[test]
+    fn is_multiple_of_codon() {
+        assert!(dna("ATG").is_multiple_of_codon());
+        assert!(!dna("ATGA").is_multiple_of_codon());
+        assert!(dna("").is_multiple_of_codon());
+    }
+
+    #[test]
+    fn pad_to_codon() {
+        let mut seq = dna("ATGA");
+        seq.pad_to_codon(Nuc::A);
+        assert_eq!(seq, dna("ATGAAA"));
+        assert_eq!(seq.len(), 6);
+        seq.pad_to_codon(Nuc::C);
+        assert_eq!(seq, dna("ATGAAA"));
+    }
+
+    #[test]
+    fn has_stop_codon() {
+        assert!(dna("ATGTAA").has_stop_codon(0));
+        assert!(!dna("ATGAAA").has_stop_codon(0));
+        assert!(!dna("ATAAGA").has_stop_codon(0));
+        assert!(dna("ATAAGA").has_stop_codon(1));
+        assert!(dna("ACTGAC").has_stop_codon(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn has_stop_codon_rejects_bad_frame() {
+        dna("TAATAA").has_stop_codon(3);
+    }
+
+    #[test]
+    fn find_orfs_on_both_strands() {
+        assert_eq!(dna("ATGAAATAG").find_orfs(1), vec![(0, 0, 2)]);
+        assert_eq!(dna("ATGAAATAG").find_orfs(3), vec![]);
+        // reverse complement is GGATGCCCTGA
+        assert_eq!(dna("TCAGGGCATCC").find_orfs(1), vec![(5, 2, 2)]);
+        assert_eq!(dna("ATGAAA").find_orfs(1), vec![]);
+        assert_eq!(dna("ATGATGTAA").find_orfs(1), vec![(0, 0, 2)]);
+    }
+
+    #[test]
+    fn max_orf_length() {
+        let seq = dna(&format!("CCATG{}TAAGG", "GCT".repeat(10)));
+        let orfs = seq.find_orfs(1);
+        assert_eq!(orfs, vec![(2, 2, 11)]);
+        assert_eq!(seq.max_orf_length(), orfs[0].2);
+        assert_eq!(dna("CCCCCC").max_orf_length(), 0);
+    }
+
+    #[test]
+    fn frames_offsets() {
+        let seq = dna("ATGCAT");
+        let [mut f0, mut f1, mut f2] = seq.frames();
+        assert_eq!(f0.next(), Some([Nuc::A, Nuc::T, Nuc::G]));
+        assert_eq!(f1.next(), Some([Nuc::T, Nuc::G, Nuc::C]));
+        assert_eq!(f2.next(), Some([Nuc::G, Nuc::C, Nuc::A]));
+        assert_eq!(f1.next(), None);
+        assert_eq!(dna("AT").frames()[0].next(), None);
+    }
+}
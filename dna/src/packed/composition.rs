@@ -0,0 +1,498 @@
+//! Base composition of [`PackedDna`].
+
+use super::{byte_len, PackedDna};
+use crate::Nuc;
+
+/// The number of bases held by a `u64` of packed data.
+const BASES_PER_WORD: usize = 32;
+
+/// The low bit of every 2-bit group in a `u64`.
+const LOW_BITS: u64 = 0x5555_5555_5555_5555;
+
+impl PackedDna {
+    /// Counts the occurrences of `target` in the sequence.
+    ///
+    /// The packed buffer is processed a `u64` (32 bases) at a time: XORing a
+    /// word with `target` broadcast to every 2-bit group turns matching groups
+    /// into `00`, which are then folded to one bit each and counted with
+    /// `count_ones`. The final word is masked to the bases it actually holds,
+    /// so whatever the padding contains is never counted.
+    pub fn count(&self, target: Nuc) -> usize {
+        let pattern = LOW_BITS * u64::from(target.to_bits());
+        self.data[..byte_len(self.len)]
+            .chunks(8)
+            .enumerate()
+            .map(|(word, chunk)| {
+                let mut bytes = [0; 8];
+                bytes[..chunk.len()].copy_from_slice(chunk);
+                let diff = u64::from_le_bytes(bytes) ^ pattern;
+                let bases = (self.len - word * BASES_PER_WORD).min(BASES_PER_WORD);
+                let in_seq = u64::MAX >> (2 * (BASES_PER_WORD - bases));
+                (!(diff | (diff >> 1)) & LOW_BITS & in_seq).count_ones() as usize
+            })
+            .sum()
+    }
+
+    /// Counts the bases for which `f` returns `true`, e.g. purines with
+    /// `|nuc| matches!(nuc, Nuc::A | Nuc::G)`.
+    ///
+    /// This decodes every base; prefer [`count`](Self::count) for a single
+    /// nucleotide.
+    pub fn count_by<F: FnMut(Nuc) -> bool>(&self, mut f: F) -> usize {
+        self.iter().filter(|&nuc| f(nuc)).count()
+    }
+
+    /// Counts each nucleotide in the sequence, returned in A, C, G, T order.
+    pub fn counts(&self) -> [usize; 4] {
+        let c = self.count(Nuc::C);
+        let g = self.count(Nuc::G);
+        let t = self.count(Nuc::T);
+        [self.len - c - g - t, c, g, t]
+    }
+
+    /// The fraction (0.0 to 1.0) of bases that are G or C, or 0.0 for an
+    /// empty sequence.
+    pub fn gc_content(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        (self.count(Nuc::G) + self.count(Nuc::C)) as f64 / self.len as f64
+    }
+
+    /// Counts every overlapping pair of adjacent bases, indexed as
+    /// `[first][second]` with bases in A, C, G, T order.
+    pub fn dinucleotide_counts(&self) -> [[usize; 4]; 4] {
+        let mut counts = [[0; 4]; 4];
+        for (first, second) in self.iter().zip(self.iter().skip(1)) {
+            counts[usize::from(first.to_bits())][usize::from(second.to_bits())] += 1;
+        }
+        counts
+    }
+
+    /// The observed/expected CpG ratio, `(CG count * len) / (C count * G
+    /// count)`, or 0.0 if the sequence has no C or no G.
+    ///
+    /// CpG islands are commonly taken to be regions where this exceeds 0.6
+    /// (along with a high GC content).
+    pub fn cpg_obs_exp(&self) -> f64 {
+        let [_, c, g, _] = self.counts();
+        if c == 0 || g == 0 {
+            return 0.0;
+        }
+        let cg = self.dinucleotide_counts()[usize::from(Nuc::C.to_bits())]
+            [usize::from(Nuc::G.to_bits())];
+        (cg * self.len) as f64 / (c * g) as f64
+    }
+
+    /// Yields the number of G and C bases in every sliding window of `window`
+    /// bases, in order of window start. Yields nothing if `window` is zero or
+    /// longer than the sequence.
+    ///
+    /// A running count is kept, adding the base entering each window and
+    /// subtracting the one leaving it, so each step is O(1) regardless of the
+    /// window size.
+    pub fn gc_windows(&self, window: usize) -> impl Iterator<Item = usize> + '_ {
+        rolling_gc(self.iter(), window)
+    }
+
+    /// Tallies the GC content of every sliding window of `window` bases into
+    /// `num_bins` equal-width bins spanning 0.0 to 1.0, for plotting the GC
+    /// distribution.
+    ///
+    /// Bin `b` covers GC fractions from `b / num_bins` up to but excluding
+    /// `(b + 1) / num_bins`, except that the last bin also takes 1.0. The
+    /// counts are all zero if there are no windows (see
+    /// [`gc_windows`](Self::gc_windows)), and empty if `num_bins` is zero.
+    pub fn gc_histogram(&self, window: usize, num_bins: usize) -> Vec<usize> {
+        let mut bins = vec![0; num_bins];
+        if num_bins == 0 {
+            return bins;
+        }
+        for gc in self.gc_windows(window) {
+            bins[(gc * num_bins / window).min(num_bins - 1)] += 1;
+        }
+        bins
+    }
+
+    /// Segments the sequence into isochores: runs of consecutive,
+    /// non-overlapping windows of `window` bases that fall in the same GC
+    /// content bin.
+    ///
+    /// `gc_breaks` are ascending bin boundaries (fractions between 0.0 and
+    /// 1.0); a window's bin index is the number of boundaries at or below its
+    /// GC content, so `n` breaks give `n + 1` bins. A shorter final window is
+    /// classified on its own. Returns half-open `(start, end, bin)` segments,
+    /// or nothing if `window` is zero.
+    pub fn isochores(&self, window: usize, gc_breaks: &[f64]) -> Vec<(usize, usize, usize)> {
+        let mut segments: Vec<(usize, usize, usize)> = Vec::new();
+        if window == 0 {
+            return segments;
+        }
+        for start in (0..self.len).step_by(window) {
+            let end = (start + window).min(self.len);
+            let gc = self.extract(start, end).gc_content();
+            let bin = gc_breaks.iter().filter(|&&brk| gc >= brk).count();
+            match segments.last_mut() {
+                Some(last) if last.2 == bin => last.1 = end,
+                _ => segments.push((start, end, bin)),
+            }
+        }
+        segments
+    }
+
+    /// The running GC skew: the cumulative sum of +1 for each G, -1 for each
+    /// C and 0 for A or T at every position. Over a bacterial genome the
+    /// curve's minimum marks the likely origin of replication.
+    pub fn cumulative_gc_skew(&self) -> Vec<f64> {
+        let mut skew = 0.0;
+        self.iter()
+            .map(|nuc| {
+                skew += match nuc {
+                    Nuc::G => 1.0,
+                    Nuc::C => -1.0,
+                    Nuc::A | Nuc::T => 0.0,
+                };
+                skew
+            })
+            .collect()
+    }
+
+    /// Whether the sequence passes basic primer checks: its length is within
+    /// `min_len..=max_len`, its GC content within the inclusive `gc_range`
+    /// (as fractions), and its [`longest_run`](Self::longest_run) at most
+    /// `max_run` bases.
+    pub fn is_valid_primer(
+        &self,
+        min_len: usize,
+        max_len: usize,
+        gc_range: (f64, f64),
+        max_run: usize,
+    ) -> bool {
+        let gc = self.gc_content();
+        (min_len..=max_len).contains(&self.len)
+            && gc >= gc_range.0
+            && gc <= gc_range.1
+            && self.longest_run() <= max_run
+    }
+
+    /// The melting temperature in °C by the Wallace rule,
+    /// `2 * (A + T) + 4 * (G + C)`.
+    ///
+    /// This is only a rough estimate, intended for short oligos of about 14
+    /// bases or fewer.
+    pub fn melting_temp(&self) -> f64 {
+        let [a, c, g, t] = self.counts();
+        (2 * (a + t) + 4 * (g + c)) as f64
+    }
+
+    /// The melting temperature in °C with a correction for the molar sodium
+    /// concentration `na_molar`:
+    ///
+    /// `Tm = 81.5 + 16.6 * log10([Na+]) + 0.41 * %GC - 600 / N`
+    ///
+    /// where `N` is the sequence length. This is more accurate than
+    /// [`melting_temp`](Self::melting_temp) for oligos of roughly 14 to 70
+    /// bases; outside that range it should be treated as indicative only.
+    /// An empty sequence gives `f64::NEG_INFINITY`.
+    pub fn melting_temp_salt(&self, na_molar: f64) -> f64 {
+        81.5 + 16.6 * na_molar.log10() + 0.41 * 100.0 * self.gc_content() - 600.0 / self.len as f64
+    }
+
+    /// The percentage (0.0 to 100.0) of A, C, G and T respectively, or all
+    /// zeros for an empty sequence.
+    pub fn composition_percent(&self) -> [f64; 4] {
+        let mut percent = [0.0; 4];
+        if self.is_empty() {
+            return percent;
+        }
+        for (percent, &count) in percent.iter_mut().zip(self.counts().iter()) {
+            *percent = 100.0 * count as f64 / self.len as f64;
+        }
+        percent
+    }
+
+    /// The Shannon entropy of the base composition, in bits.
+    ///
+    /// Ranges from 0.0 for a homopolymer (or an empty sequence) to 2.0 when
+    /// all four bases are equally frequent.
+    pub fn shannon_entropy(&self) -> f64 {
+        let len = self.len as f64;
+        self.counts()
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / len;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Finds low-complexity stretches: every sliding window of `window` bases
+    /// whose [`shannon_entropy`](Self::shannon_entropy) is below
+    /// `entropy_threshold` is flagged, and overlapping or touching flagged
+    /// windows are merged into half-open `(start, end)` ranges.
+    pub fn low_complexity_regions(
+        &self,
+        window: usize,
+        entropy_threshold: f64,
+    ) -> Vec<(usize, usize)> {
+        let mut regions: Vec<(usize, usize)> = Vec::new();
+        if window == 0 || window > self.len {
+            return regions;
+        }
+        for start in 0..=self.len - window {
+            let end = start + window;
+            if self.extract(start, end).shannon_entropy() >= entropy_threshold {
+                continue;
+            }
+            match regions.last_mut() {
+                Some(last) if last.1 >= start => last.1 = end,
+                _ => regions.push((start, end)),
+            }
+        }
+        regions
+    }
+}
+
+/// The rolling GC count behind [`PackedDna::gc_windows`], generic over the
+/// base iterator so the tests can count how many bases it reads.
+fn rolling_gc<I>(bases: I, window: usize) -> impl Iterator<Item = usize>
+where
+    I: ExactSizeIterator<Item = Nuc> + Clone,
+{
+    let is_gc = |nuc: Nuc| usize::from(matches!(nuc, Nuc::C | Nuc::G));
+    let len = bases.len();
+    let num_windows = if window == 0 || window > len {
+        0
+    } else {
+        len - window + 1
+    };
+    let mut gc: usize = bases.clone().take(window).map(is_gc).sum();
+    let first = gc;
+    let steps = bases
+        .clone()
+        .zip(bases.skip(window))
+        .map(move |(leaving, entering)| {
+            gc = gc + is_gc(entering) - is_gc(leaving);
+            gc
+        });
+    std::iter::once(first).chain(steps).take(num_windows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small LCG so the tests can cover many sequences without `rand`.
+    fn pseudo_random(len: usize, seed: u64) -> PackedDna {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                Nuc::from_bits((state >> 33) as u8)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn count() {
+        let seq: PackedDna = "ACGTTGA".parse().unwrap();
+        assert_eq!(seq.count(Nuc::A), 2);
+        assert_eq!(seq.count(Nuc::T), 2);
+        assert_eq!(seq.counts(), [2, 1, 2, 2]);
+        assert_eq!(PackedDna::new().counts(), [0; 4]);
+    }
+
+    #[test]
+    fn count_ignores_dirty_padding() {
+        let clean: PackedDna = "ACGTAC".parse().unwrap();
+        let mut dirty = clean.clone();
+        dirty.data[1] |= 0b1111_0000;
+        dirty.data.extend_from_slice(&[0xff; 9]);
+        for &nuc in Nuc::ALL.iter() {
+            assert_eq!(dirty.count(nuc), clean.count(nuc), "{:?}", nuc);
+        }
+        assert_eq!(dirty.counts(), [2, 2, 1, 1]);
+    }
+
+    #[test]
+    fn count_by() {
+        let seq: PackedDna = "ACGT".parse().unwrap();
+        assert_eq!(seq.count_by(|nuc| matches!(nuc, Nuc::A | Nuc::G)), 2);
+        assert_eq!(seq.count_by(|_| false), 0);
+        let seq = pseudo_random(1000, 3);
+        assert_eq!(seq.count_by(|nuc| nuc == Nuc::T), seq.count(Nuc::T));
+    }
+
+    #[test]
+    fn gc_content() {
+        let gc = |s: &str| s.parse::<PackedDna>().unwrap().gc_content();
+        assert_eq!(gc("ACGT"), 0.5);
+        assert_eq!(gc("GGCCA"), 0.8);
+        assert_eq!(gc(""), 0.0);
+    }
+
+    #[test]
+    fn dinucleotide_counts() {
+        let seq: PackedDna = "ACGCG".parse().unwrap();
+        let counts = seq.dinucleotide_counts();
+        assert_eq!(counts[0][1], 1);
+        assert_eq!(counts[1][2], 2);
+        assert_eq!(counts[2][1], 1);
+        assert_eq!(counts.iter().flatten().sum::<usize>(), 4);
+        assert_eq!(PackedDna::new().dinucleotide_counts(), [[0; 4]; 4]);
+    }
+
+    #[test]
+    fn cpg_obs_exp() {
+        let ratio = |s: &str| s.parse::<PackedDna>().unwrap().cpg_obs_exp();
+        assert_eq!(ratio("CGCGCGCG"), 2.0);
+        assert!(ratio("ACGTTCGAGCGCATCG") > 0.6);
+        assert!(ratio("CCAGGTCAGGACTGCA") < 0.6);
+        assert_eq!(ratio("ATATCC"), 0.0);
+    }
+
+    #[test]
+    fn gc_windows() {
+        let seq: PackedDna = "AGCTTGCA".parse().unwrap();
+        let windows: Vec<usize> = seq.gc_windows(3).collect();
+        assert_eq!(windows, vec![2, 2, 1, 1, 2, 2]);
+        assert_eq!(seq.gc_windows(8).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(seq.gc_windows(9).count(), 0);
+        assert_eq!(seq.gc_windows(0).count(), 0);
+    }
+
+    #[test]
+    fn gc_windows_matches_naive_on_1m_bases() {
+        let window = 100;
+        let seq = pseudo_random(1_000_000, 17);
+        let bases = seq.to_vec();
+        let naive = bases.windows(window).map(|w| {
+            w.iter()
+                .filter(|&&nuc| nuc == Nuc::C || nuc == Nuc::G)
+                .count()
+        });
+        let mut compared = 0;
+        for (rolling, expected) in seq.gc_windows(window).zip(naive) {
+            assert_eq!(rolling, expected);
+            compared += 1;
+        }
+        assert_eq!(compared, seq.len() - window + 1);
+        assert_eq!(seq.gc_windows(window).count(), compared);
+    }
+
+    #[test]
+    fn gc_windows_reads_each_base_twice_whatever_the_window() {
+        let seq = pseudo_random(1_000_000, 17);
+        for &window in &[100, 10_000] {
+            let reads = std::cell::Cell::new(0);
+            let bases = seq.iter().inspect(|_| reads.set(reads.get() + 1));
+            let windows = rolling_gc(bases, window).count();
+            assert_eq!(windows, seq.len() - window + 1);
+            // the entering side walks the whole sequence once, and the
+            // initial sum followed by the leaving side walks it once more
+            assert_eq!(reads.get(), 2 * seq.len(), "window {}", window);
+        }
+    }
+
+    #[test]
+    fn gc_histogram() {
+        let seq: PackedDna = "AAGCGCAT".parse().unwrap();
+        // windows of 4: AAGC 0.5, AGCG 0.75, GCGC 1.0, CGCA 0.75, GCAT 0.5
+        assert_eq!(seq.gc_histogram(4, 4), vec![0, 0, 2, 3]);
+        assert_eq!(seq.gc_histogram(4, 2), vec![0, 5]);
+        assert_eq!(seq.gc_histogram(2, 3), vec![2, 2, 3]);
+        assert_eq!(seq.gc_histogram(9, 3), vec![0, 0, 0]);
+        assert!(seq.gc_histogram(4, 0).is_empty());
+    }
+
+    #[test]
+    fn isochores_split_gc_rich_and_poor_blocks() {
+        let seq: PackedDna = format!("{}{}", "GC".repeat(10), "AT".repeat(10))
+            .parse()
+            .unwrap();
+        assert_eq!(seq.isochores(5, &[0.5]), vec![(0, 20, 1), (20, 40, 0)]);
+        assert_eq!(seq.isochores(40, &[0.4, 0.6]), vec![(0, 40, 1)]);
+        assert!(seq.isochores(0, &[0.5]).is_empty());
+    }
+
+    #[test]
+    fn cumulative_gc_skew() {
+        let seq: PackedDna = "GGCC".parse().unwrap();
+        assert_eq!(seq.cumulative_gc_skew(), vec![1.0, 2.0, 1.0, 0.0]);
+        let seq: PackedDna = "ACTG".parse().unwrap();
+        assert_eq!(seq.cumulative_gc_skew(), vec![0.0, -1.0, -1.0, 0.0]);
+    }
+
+    #[test]
+    fn is_valid_primer() {
+        let valid = |s: &str| {
+            s.parse::<PackedDna>()
+                .unwrap()
+                .is_valid_primer(18, 25, (0.4, 0.6), 4)
+        };
+        assert!(valid("AGCTGACCTGAAGTCGATCG"));
+        assert!(!valid("AGCTGACCTGAAG"));
+        assert!(!valid("GCGCGGCCGCGAGCGCCGCG"));
+        assert!(!valid("AGCTGAAAAAAGGTCGCTCG"));
+    }
+
+    #[test]
+    fn melting_temp() {
+        let seq: PackedDna = "ACGTGC".parse().unwrap();
+        assert_eq!(seq.melting_temp(), 20.0);
+    }
+
+    #[test]
+    fn melting_temp_salt_20mer() {
+        // 50% GC at 50 mM Na+:
+        // 81.5 + 16.6 * log10(0.05) + 0.41 * 50 - 600 / 20 = 50.403
+        let seq: PackedDna = "ACGTACGTACGTACGTACGT".parse().unwrap();
+        assert!((seq.melting_temp_salt(0.05) - 50.403).abs() < 1e-3);
+        let gc_rich: PackedDna = "GCGCGCGCGCACGTACGTAT".parse().unwrap();
+        assert!(gc_rich.melting_temp_salt(0.05) > seq.melting_temp_salt(0.05));
+    }
+
+    #[test]
+    fn composition_percent() {
+        let seq: PackedDna = "AACG".parse().unwrap();
+        assert_eq!(seq.composition_percent(), [50.0, 25.0, 25.0, 0.0]);
+        assert_eq!(PackedDna::new().composition_percent(), [0.0; 4]);
+    }
+
+    #[test]
+    fn shannon_entropy() {
+        let entropy = |s: &str| s.parse::<PackedDna>().unwrap().shannon_entropy();
+        assert_eq!(entropy(""), 0.0);
+        assert_eq!(entropy("AAAA"), 0.0);
+        assert!((entropy("ACGT") - 2.0).abs() < 1e-12);
+        assert!((entropy("AACC") - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn low_complexity_regions_flag_poly_a() {
+        let seq: PackedDna = format!("{}{}", "A".repeat(20), "ACGT".repeat(5))
+            .parse()
+            .unwrap();
+        assert_eq!(seq.low_complexity_regions(8, 1.0), vec![(0, 22)]);
+        let diverse: PackedDna = "ACGT".repeat(10).parse().unwrap();
+        assert!(diverse.low_complexity_regions(8, 1.0).is_empty());
+    }
+
+    #[test]
+    fn counts_match_naive_scan() {
+        for (seed, len) in (0..40).map(|seed| (seed, seed as usize * 7 + 1)) {
+            let seq = pseudo_random(len, seed);
+            let mut naive = [0; 4];
+            for nuc in seq.iter() {
+                naive[nuc.to_bits() as usize] += 1;
+            }
+            assert_eq!(seq.counts(), naive, "len {}", len);
+            for (nuc, &expected) in Nuc::ALL.iter().zip(naive.iter()) {
+                assert_eq!(seq.count(*nuc), expected, "len {} {:?}", len, nuc);
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Parsing of gapped alignment rows into [`PackedDna`].
+
+use std::{convert::TryFrom, str::FromStr};
+
+use super::{PackedDna, ParseDnaError};
+use crate::Nuc;
+
+/// The character marking a gap in an alignment row.
+const GAP: char = '-';
+
+/// A row of an alignment: the real bases, packed, plus the positions of the
+/// `-` gap characters that were removed to pack them.
+///
+/// Gaps cannot be stored in a [`PackedDna`], so parsing a `GappedDna` is the
+/// lenient counterpart to parsing a `PackedDna`, which rejects `-`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct GappedDna {
+    seq: PackedDna,
+    gaps: Vec<usize>,
+}
+
+impl GappedDna {
+    /// The bases of the row with the gaps removed.
+    pub fn seq(&self) -> &PackedDna {
+        &self.seq
+    }
+
+    /// The positions of the gaps in the original row, in increasing order.
+    pub fn gap_positions(&self) -> &[usize] {
+        &self.gaps
+    }
+
+    /// The length of the original row, counting gaps.
+    pub fn aligned_len(&self) -> usize {
+        self.seq.len() + self.gaps.len()
+    }
+
+    /// Discards the gap positions, keeping only the bases.
+    pub fn into_seq(self) -> PackedDna {
+        self.seq
+    }
+}
+
+impl FromStr for GappedDna {
+    type Err = ParseDnaError;
+
+    /// Parses an alignment row, case insensitively, recording each `-`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut gapped = Self::default();
+        for (index, character) in s.chars().enumerate() {
+            if character == GAP {
+                gapped.gaps.push(index);
+                continue;
+            }
+            let nuc = Nuc::try_from(character).map_err(|_| ParseDnaError { index, character })?;
+            gapped.seq.push(nuc);
+        }
+        Ok(gapped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gaps() {
+        let gapped: GappedDna = "AC-GT".parse().unwrap();
+        assert_eq!(gapped.seq(), &"ACGT".parse::<PackedDna>().unwrap());
+        assert_eq!(gapped.gap_positions(), &[2]);
+        assert_eq!(gapped.aligned_len(), 5);
+    }
+
+    #[test]
+    fn strict_parser_rejects_gaps() {
+        assert!("AC-GT".parse::<PackedDna>().is_err());
+        let err = "A-C-x".parse::<GappedDna>().unwrap_err();
+        assert_eq!(err.index(), 4);
+    }
+}
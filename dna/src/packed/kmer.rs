@@ -0,0 +1,534 @@
+//! K-mer operations on [`PackedDna`].
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::PackedDna;
+use crate::Nuc;
+
+/// The largest k-mer that fits in a `u64` at two bits per base.
+pub(crate) const MAX_U64_K: usize = 32;
+
+/// The largest `k` accepted by [`PackedDna::kmer_spectrum`].
+const MAX_SPECTRUM_K: usize = 8;
+
+impl PackedDna {
+    /// Returns an iterator over every overlapping `k`-mer, in order.
+    ///
+    /// Nothing is yielded if `k` is zero or longer than the sequence.
+    pub fn kmers(&self, k: usize) -> impl Iterator<Item = PackedDna> + '_ {
+        let count = if k == 0 || k > self.len {
+            0
+        } else {
+            self.len - k + 1
+        };
+        (0..count).map(move |start| self.extract(start, start + k))
+    }
+
+    /// Returns an iterator over the `k`-mers of the sequence read as a circle:
+    /// like [`kmers`](Self::kmers), but also yielding the `k - 1` windows that
+    /// wrap from the end back to the start, so every position begins a
+    /// `k`-mer.
+    ///
+    /// Nothing is yielded if `k` is zero or longer than the sequence.
+    pub fn kmers_circular(&self, k: usize) -> impl Iterator<Item = PackedDna> {
+        let count = if k == 0 || k > self.len { 0 } else { self.len };
+        let mut wrapped = self.clone();
+        if count > 0 {
+            wrapped.append(&self.extract(0, k - 1));
+        }
+        (0..count).map(move |start| wrapped.extract(start, start + k))
+    }
+
+    /// Returns the De Bruijn graph edge of every `k`-mer, in order: its
+    /// `(k - 1)`-base prefix to its `(k - 1)`-base suffix.
+    ///
+    /// There is one edge per `k`-mer, so repeated `k`-mers give repeated
+    /// edges. Nothing is returned if `k` is less than 2 or longer than the
+    /// sequence.
+    pub fn debruijn_edges(&self, k: usize) -> Vec<(PackedDna, PackedDna)> {
+        if k < 2 {
+            return Vec::new();
+        }
+        self.kmers(k)
+            .map(|kmer| (kmer.extract(0, k - 1), kmer.extract(1, k)))
+            .collect()
+    }
+
+    /// Counts the occurrences of every distinct `k`-mer.
+    pub fn kmer_counts(&self, k: usize) -> HashMap<PackedDna, usize> {
+        let mut counts = HashMap::new();
+        for kmer in self.kmers(k) {
+            *counts.entry(kmer).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Counts the occurrences of every distinct `k`-mer, keyed by its 2-bit
+    /// encoding in a `u64` (the first base in the most significant bits).
+    ///
+    /// This is much cheaper than [`kmer_counts`](Self::kmer_counts) for small
+    /// `k`, as no `PackedDna` is allocated per `k`-mer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or greater than 32.
+    pub fn kmer_u64_counts(&self, k: usize) -> HashMap<u64, usize> {
+        let mut counts = HashMap::new();
+        for (_, code) in self.kmer_codes(k) {
+            *counts.entry(code).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// The number of distinct `k`-mers in the sequence, or 0 if `k` is zero or
+    /// greater than the length.
+    ///
+    /// For `k` up to 32 the `k`-mers are kept as `u64` codes; longer ones fall
+    /// back to `PackedDna` keys.
+    pub fn distinct_kmers(&self, k: usize) -> usize {
+        if k == 0 {
+            0
+        } else if k <= MAX_U64_K {
+            let codes: HashSet<u64> = self.kmer_codes(k).map(|(_, code)| code).collect();
+            codes.len()
+        } else {
+            self.kmers(k).collect::<HashSet<_>>().len()
+        }
+    }
+
+    /// The `k`-mer feature vector: a `4^k` long vector whose entry at each
+    /// `k`-mer's 2-bit encoding (first base most significant, so entries are
+    /// in lexicographic order) is how often that `k`-mer occurs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or greater than 8, which keeps the vector to at
+    /// most 65536 entries.
+    pub fn kmer_spectrum(&self, k: usize) -> Vec<usize> {
+        assert!(
+            (1..=MAX_SPECTRUM_K).contains(&k),
+            "k must be between 1 and {}, got {}",
+            MAX_SPECTRUM_K,
+            k
+        );
+        let mut spectrum = vec![0; 1 << (2 * k)];
+        for (_, code) in self.kmer_codes(k) {
+            spectrum[code as usize] += 1;
+        }
+        spectrum
+    }
+
+    /// The cosine similarity of the [`kmer_spectrum`](Self::kmer_spectrum)
+    /// vectors of `self` and `other`, from 0.0 (no shared `k`-mers) to 1.0
+    /// (proportional counts), or 0.0 if either has no `k`-mers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or greater than 8.
+    pub fn kmer_cosine_similarity(&self, other: &PackedDna, k: usize) -> f64 {
+        let ours = self.kmer_spectrum(k);
+        let theirs = other.kmer_spectrum(k);
+        let norm = |spectrum: &[usize]| {
+            spectrum
+                .iter()
+                .map(|&count| (count * count) as f64)
+                .sum::<f64>()
+                .sqrt()
+        };
+        let norms = norm(&ours) * norm(&theirs);
+        if norms == 0.0 {
+            return 0.0;
+        }
+        let dot: f64 = ours
+            .iter()
+            .zip(&theirs)
+            .map(|(&a, &b)| (a * b) as f64)
+            .sum();
+        dot / norms
+    }
+
+    /// The Jaccard index of the distinct `k`-mer sets of `self` and `other`,
+    /// defined as 1.0 when both sets are empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or greater than 32.
+    pub fn kmer_jaccard(&self, other: &PackedDna, k: usize) -> f64 {
+        let ours: HashSet<u64> = self.kmer_codes(k).map(|(_, code)| code).collect();
+        let theirs: HashSet<u64> = other.kmer_codes(k).map(|(_, code)| code).collect();
+        let union = ours.union(&theirs).count();
+        if union == 0 {
+            return 1.0;
+        }
+        ours.intersection(&theirs).count() as f64 / union as f64
+    }
+
+    /// Builds a bottom-`num_hashes` MinHash sketch: the smallest distinct
+    /// `k`-mer hashes, in increasing order.
+    ///
+    /// Sketches of different sequences can be compared with
+    /// [`sketch::similarity`](crate::sketch::similarity) to estimate the
+    /// Jaccard index of their `k`-mer sets without storing the sets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or greater than 32.
+    pub fn minhash(&self, k: usize, num_hashes: usize) -> Vec<u64> {
+        let mut hashes: Vec<u64> = self
+            .kmer_codes(k)
+            .map(|(_, code)| hash_kmer(code))
+            .collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        hashes.truncate(num_hashes);
+        hashes
+    }
+
+    /// Encodes the sequence as a `u64`, two bits per base with the first base
+    /// in the most significant used bits, or `None` if it is longer than 32
+    /// bases.
+    ///
+    /// This is the same encoding used by
+    /// [`kmer_u64_counts`](Self::kmer_u64_counts), so integer order matches
+    /// lexicographic order for sequences of equal length.
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.len > MAX_U64_K {
+            return None;
+        }
+        Some(
+            self.iter()
+                .fold(0, |code, nuc| (code << 2) | u64::from(nuc.to_bits())),
+        )
+    }
+
+    /// Decodes a sequence of `len` bases from the encoding produced by
+    /// [`to_u64`](Self::to_u64). Bits above the lowest `2 * len` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than 32.
+    pub fn from_u64(value: u64, len: usize) -> PackedDna {
+        assert!(
+            len <= MAX_U64_K,
+            "len must be at most {}, got {}",
+            MAX_U64_K,
+            len
+        );
+        (0..len)
+            .rev()
+            .map(|idx| Nuc::from_bits((value >> (2 * idx)) as u8))
+            .collect()
+    }
+
+    /// Returns the minimizer of every window of `w` consecutive `k`-mers.
+    ///
+    /// Each item is the position and hash of the `k`-mer with the smallest
+    /// hash in its window, the leftmost one winning ties. One item is yielded
+    /// per window, so neighbouring windows often repeat a minimizer; positions
+    /// never decrease. Nothing is yielded if `w` is zero or the sequence holds
+    /// fewer than `w` `k`-mers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or greater than 32.
+    pub fn minimizers(&self, k: usize, w: usize) -> impl Iterator<Item = (usize, u64)> + '_ {
+        let mut hashes = self.kmer_codes(k).map(|(pos, code)| (pos, hash_kmer(code)));
+        let mut window: VecDeque<(usize, u64)> = VecDeque::new();
+        std::iter::from_fn(move || {
+            if w == 0 {
+                return None;
+            }
+            loop {
+                let (pos, hash) = hashes.next()?;
+                while window.back().map_or(false, |&(_, last)| last > hash) {
+                    window.pop_back();
+                }
+                window.push_back((pos, hash));
+                while window.front().map_or(false, |&(first, _)| first + w <= pos) {
+                    window.pop_front();
+                }
+                if pos + 1 >= w {
+                    return window.front().copied();
+                }
+            }
+        })
+    }
+
+    /// Rolls over the sequence, yielding each `k`-mer's position and its
+    /// 2-bit encoding with the first base in the most significant bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or greater than 32.
+    pub(crate) fn kmer_codes(&self, k: usize) -> impl Iterator<Item = (usize, u64)> + '_ {
+        assert!(
+            (1..=MAX_U64_K).contains(&k),
+            "k must be between 1 and {}, got {}",
+            MAX_U64_K,
+            k
+        );
+        let mask = u64::MAX >> (64 - 2 * k);
+        let mut code = 0;
+        self.iter().enumerate().filter_map(move |(idx, nuc)| {
+            code = ((code << 2) | u64::from(nuc.to_bits())) & mask;
+            if idx + 1 >= k {
+                Some((idx + 1 - k, code))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Reverse-complements a `k`-mer in the 2-bit `u64` encoding of
+/// [`PackedDna::to_u64`], using only bit operations.
+///
+/// The bases are complemented by inverting every bit, then the 2-bit groups
+/// are reversed by swapping pairs, nibbles and finally bytes, leaving the
+/// result in the top `2 * k` bits to be shifted down. Bits above the lowest
+/// `2 * k` of `value` are ignored.
+///
+/// # Panics
+///
+/// Panics if `k` is greater than 32.
+pub fn revcomp_u64(value: u64, k: usize) -> u64 {
+    assert!(k <= MAX_U64_K, "k must be at most {}, got {}", MAX_U64_K, k);
+    if k == 0 {
+        return 0;
+    }
+    let mut x = !value;
+    x = ((x >> 2) & 0x3333_3333_3333_3333) | ((x & 0x3333_3333_3333_3333) << 2);
+    x = ((x >> 4) & 0x0f0f_0f0f_0f0f_0f0f) | ((x & 0x0f0f_0f0f_0f0f_0f0f) << 4);
+    x.swap_bytes() >> (64 - 2 * k)
+}
+
+/// Scrambles a 2-bit k-mer encoding so that hash order is independent of
+/// lexicographic order (the splitmix64 finalizer).
+pub(crate) fn hash_kmer(code: u64) -> u64 {
+    let mut x = code;
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn dna(s: &str) -> PackedDna {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn kmer_codes_roll() {
+        let codes: Vec<_> = dna("ACGTA").kmer_codes(2).collect();
+        assert_eq!(
+            codes,
+            vec![(0, 0b00_01), (1, 0b01_10), (2, 0b10_11), (3, 0b11_00)]
+        );
+        assert_eq!(dna("A").kmer_codes(2).count(), 0);
+    }
+
+    #[test]
+    fn kmers() {
+        let kmers: Vec<String> = dna("ACGTA").kmers(3).map(|k| k.to_string()).collect();
+        assert_eq!(kmers, vec!["ACG", "CGT", "GTA"]);
+        assert_eq!(dna("ACG").kmers(4).count(), 0);
+        assert_eq!(dna("ACG").kmers(0).count(), 0);
+    }
+
+    #[test]
+    fn kmers_circular() {
+        let kmers: Vec<String> = dna("ACGT")
+            .kmers_circular(2)
+            .map(|k| k.to_string())
+            .collect();
+        assert_eq!(kmers, vec!["AC", "CG", "GT", "TA"]);
+        let kmers: Vec<String> = dna("ACG")
+            .kmers_circular(3)
+            .map(|k| k.to_string())
+            .collect();
+        assert_eq!(kmers, vec!["ACG", "CGA", "GAC"]);
+        assert_eq!(dna("ACG").kmers_circular(4).count(), 0);
+    }
+
+    #[test]
+    fn debruijn_edges() {
+        let seq = dna("ACGTG");
+        let edges: Vec<(String, String)> = seq
+            .debruijn_edges(3)
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect();
+        let expected = [("AC", "CG"), ("CG", "GT"), ("GT", "TG")];
+        assert_eq!(edges.len(), seq.len() - 3 + 1);
+        for (edge, (from, to)) in edges.iter().zip(expected.iter()) {
+            assert_eq!((edge.0.as_str(), edge.1.as_str()), (*from, *to));
+        }
+        assert!(seq.debruijn_edges(1).is_empty());
+    }
+
+    #[test]
+    fn kmer_counts() {
+        let counts = dna("AAAC").kmer_counts(2);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&dna("AA")], 2);
+        assert_eq!(counts[&dna("AC")], 1);
+    }
+
+    #[test]
+    fn kmer_u64_counts_match_kmer_counts() {
+        let seq = dna("ACGTTACGGACGTA");
+        let by_u64 = seq.kmer_u64_counts(3);
+        let by_dna = seq.kmer_counts(3);
+        assert_eq!(by_u64.len(), by_dna.len());
+        for (kmer, count) in by_dna {
+            let (_, code) = kmer.kmer_codes(3).next().unwrap();
+            assert_eq!(by_u64[&code], count);
+        }
+    }
+
+    #[test]
+    fn distinct_kmers() {
+        assert_eq!(dna("AAAA").distinct_kmers(2), 1);
+        assert_eq!(dna("ACGT").distinct_kmers(2), 3);
+        assert_eq!(dna("ACGT").distinct_kmers(5), 0);
+        assert_eq!(dna("ACGT").distinct_kmers(0), 0);
+        let long = dna(&"ACGTTGCA".repeat(6));
+        assert_eq!(long.distinct_kmers(33), long.kmer_counts(33).len());
+        assert_eq!(long.distinct_kmers(33), 8);
+    }
+
+    #[test]
+    fn kmer_spectrum() {
+        assert_eq!(dna("ACGT").kmer_spectrum(1), vec![1, 1, 1, 1]);
+        let spectrum = dna("AACGAAC").kmer_spectrum(2);
+        assert_eq!(spectrum.len(), 16);
+        // AA = 0b0000, AC = 0b0001, CG = 0b0110, GA = 0b1000
+        assert_eq!(
+            (spectrum[0], spectrum[1], spectrum[6], spectrum[8]),
+            (2, 2, 1, 1)
+        );
+        assert_eq!(spectrum.iter().sum::<usize>(), 6);
+        assert_eq!(dna("A").kmer_spectrum(3), vec![0; 64]);
+    }
+
+    #[test]
+    fn kmer_cosine_similarity() {
+        let seq = dna("ACGTTGCAAGCTTAGGC");
+        assert!((seq.kmer_cosine_similarity(&seq, 3) - 1.0).abs() < 1e-12);
+        let other = dna("ACGTTGCAATTTTTTTT");
+        let score = seq.kmer_cosine_similarity(&other, 3);
+        assert!(score > 0.0 && score < 1.0);
+        assert_eq!(dna("AAAA").kmer_cosine_similarity(&dna("CCCC"), 2), 0.0);
+        assert_eq!(dna("A").kmer_cosine_similarity(&seq, 2), 0.0);
+        // AA twice and AC once against AA once: 2 / (sqrt(5) * 1)
+        let score = dna("AAAC").kmer_cosine_similarity(&dna("AA"), 2);
+        assert!((score - 2.0 / 5f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn kmer_spectrum_rejects_large_k() {
+        dna("ACGT").kmer_spectrum(9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn kmer_u64_counts_rejects_large_k() {
+        dna("ACGT").kmer_u64_counts(33);
+    }
+
+    #[test]
+    fn kmer_jaccard() {
+        let seq = dna("ACGTTGCA");
+        assert_eq!(seq.kmer_jaccard(&seq, 3), 1.0);
+        assert_eq!(dna("AAAAA").kmer_jaccard(&dna("CCCCC"), 2), 0.0);
+        assert_eq!(dna("A").kmer_jaccard(&dna("C"), 2), 1.0);
+        // {AC, CG} vs {CG, GT}
+        assert!((dna("ACG").kmer_jaccard(&dna("CGT"), 2) - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn u64_round_trip() {
+        for s in ["", "A", "ACGT", "TTGCA", "ACGTACGTACGTACGTACGTACGTACGTACGT"].iter() {
+            let seq = dna(s);
+            let code = seq.to_u64().unwrap();
+            assert_eq!(PackedDna::from_u64(code, seq.len()), seq);
+        }
+        assert_eq!(dna("CT").to_u64(), Some(0b01_11));
+        assert_eq!(PackedDna::from_u64(u64::MAX, 2), dna("TT"));
+    }
+
+    #[test]
+    fn to_u64_rejects_long_sequences() {
+        let seq: PackedDna = std::iter::repeat(Nuc::G).take(33).collect();
+        assert_eq!(seq.to_u64(), None);
+    }
+
+    #[test]
+    fn revcomp_u64_matches_decoding() {
+        let mut value: u64 = 0x9e37_79b9_7f4a_7c15;
+        for round in 0..200 {
+            value = value
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let k = round % 33;
+            let expected = PackedDna::from_u64(value, k)
+                .reverse_complement()
+                .to_u64()
+                .unwrap();
+            assert_eq!(revcomp_u64(value, k), expected, "k {}", k);
+        }
+        assert_eq!(
+            revcomp_u64(dna("AACG").to_u64().unwrap(), 4),
+            dna("CGTT").to_u64().unwrap()
+        );
+    }
+
+    #[test]
+    fn minimizers_match_naive() {
+        let seq = dna("ACGTTGCATGCAAGCTTAGGCATCGA");
+        let (k, w) = (4, 5);
+        let hashes: Vec<u64> = seq.kmer_codes(k).map(|(_, c)| hash_kmer(c)).collect();
+        let naive: Vec<(usize, u64)> = hashes
+            .windows(w)
+            .enumerate()
+            .map(|(start, window)| {
+                let (offset, hash) = window
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|&(offset, hash)| (*hash, offset))
+                    .unwrap();
+                (start + offset, *hash)
+            })
+            .collect();
+        let actual: Vec<(usize, u64)> = seq.minimizers(k, w).collect();
+        assert_eq!(actual, naive);
+        assert!(actual.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    }
+
+    #[test]
+    fn minimizers_of_periodic_sequence() {
+        // every window of three 3-mers holds each of ACG, CGA and GAC once
+        let seq = dna("ACGACGACG");
+        let distinct: HashSet<u64> = seq.minimizers(3, 3).map(|(_, hash)| hash).collect();
+        let expected = ["ACG", "CGA", "GAC"]
+            .iter()
+            .map(|kmer| hash_kmer(dna(kmer).kmer_codes(3).next().unwrap().1))
+            .min()
+            .unwrap();
+        assert_eq!(distinct, [expected].iter().copied().collect());
+        let positions: Vec<usize> = seq.minimizers(3, 3).map(|(pos, _)| pos).collect();
+        assert_eq!(positions.len(), 5);
+        assert!(positions.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn minimizers_need_a_full_window() {
+        assert_eq!(dna("ACGT").minimizers(3, 3).count(), 0);
+        assert_eq!(dna("ACGT").minimizers(2, 0).count(), 0);
+    }
+}
@@ -0,0 +1,85 @@
+//! Loading [`PackedDna`] from memory-mapped files, behind the `mmap` feature.
+
+use std::{fs::File, io, path::Path};
+
+use memmap2::Mmap;
+
+use super::{byte_len, clear_padding, PackedDna};
+
+impl PackedDna {
+    /// Reads a sequence of `len` bases from a file of raw packed bytes, in
+    /// the layout described in [`packed_chunks`](Self::packed_chunks), such
+    /// as one written from those chunks.
+    ///
+    /// The file is memory-mapped and the `len.div_ceil(4)` bytes needed are
+    /// copied out of the mapping, so the kernel pages the file in on demand
+    /// rather than it being read through an intermediate buffer. The returned
+    /// sequence owns its copy: the mapping is dropped before this returns and
+    /// later changes to the file do not affect it. Any bytes past those needed
+    /// are ignored, and padding bits in the final byte are cleared.
+    ///
+    /// Fails with [`io::ErrorKind::UnexpectedEof`] if the file is too short
+    /// to hold `len` bases.
+    pub fn from_mmap_packed<P: AsRef<Path>>(path: P, len: usize) -> io::Result<PackedDna> {
+        let file = File::open(path)?;
+        let needed = byte_len(len);
+        if needed == 0 {
+            return Ok(PackedDna::new());
+        }
+        // SAFETY: the mapping is only read while copied out below and is
+        // dropped before returning; the caller must not truncate the file
+        // concurrently, as with any memory-mapped read.
+        let map = unsafe { Mmap::map(&file)? };
+        let bytes = map.get(..needed).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "{} bases need {} bytes, but the file has {}",
+                    len,
+                    needed,
+                    map.len()
+                ),
+            )
+        })?;
+        let mut data = bytes.to_vec();
+        clear_padding(&mut data, len);
+        Ok(PackedDna { data, len })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("dna-mmap-{}-{}.bin", name, std::process::id()))
+    }
+
+    #[test]
+    fn round_trips_packed_bytes() {
+        let seq: PackedDna = "ACGTTGCAAGCTTAGGC".parse().unwrap();
+        let path = temp_path("round-trip");
+        fs::write(&path, seq.packed_chunks(4).collect::<Vec<_>>().concat()).unwrap();
+        let whole = PackedDna::from_mmap_packed(&path, seq.len());
+        let prefix = PackedDna::from_mmap_packed(&path, 6);
+        let too_long = PackedDna::from_mmap_packed(&path, 21);
+        let empty = PackedDna::from_mmap_packed(&path, 0);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(whole.unwrap(), seq);
+        let prefix = prefix.unwrap();
+        assert_eq!(prefix, seq.trim_3prime(6));
+        assert!(prefix.validate());
+        assert_eq!(too_long.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert!(empty.unwrap().is_empty());
+    }
+
+    #[test]
+    fn missing_file() {
+        let path = temp_path("does-not-exist");
+        let err = PackedDna::from_mmap_packed(path, 4).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
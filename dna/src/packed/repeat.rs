@@ -0,0 +1,236 @@
+//! Repeat detection within [`PackedDna`].
+
+use std::collections::HashMap;
+
+use super::PackedDna;
+use crate::Nuc;
+
+impl PackedDna {
+    /// Finds tandem repeats: regions made of at least `min_copies`
+    /// back-to-back copies of some `unit_len`-base unit.
+    ///
+    /// Returns `(start, copies)` pairs. The scan is greedy from left to right,
+    /// so once a repeat is reported the search resumes after its last full
+    /// copy. A single copy is not a repeat, so `min_copies` below 2 is treated
+    /// as 2. Nothing is found if `unit_len` is zero.
+    pub fn tandem_repeats(&self, unit_len: usize, min_copies: usize) -> Vec<(usize, usize)> {
+        let mut repeats = Vec::new();
+        if unit_len == 0 {
+            return repeats;
+        }
+        let mut start = 0;
+        while start + unit_len <= self.len {
+            let mut end = start + unit_len;
+            while end < self.len && self.get_unchecked(end) == self.get_unchecked(end - unit_len) {
+                end += 1;
+            }
+            let copies = (end - start) / unit_len;
+            if copies >= min_copies.max(2) {
+                repeats.push((start, copies));
+                start += copies * unit_len;
+            } else {
+                start += 1;
+            }
+        }
+        repeats
+    }
+
+    /// Finds inverted repeats (hairpins): a stem of at least `min_stem` bases
+    /// followed, after a loop of at most `max_loop` bases, by its reverse
+    /// complement.
+    ///
+    /// Returns `(stem_start, loop_start, stem_len)` triples, ordered by loop
+    /// position. Each stem is extended as far as it pairs. When the loop's
+    /// outermost bases pair with each other, the hairpin is only reported
+    /// with the longer stem and shorter loop.
+    pub fn inverted_repeats(&self, min_stem: usize, max_loop: usize) -> Vec<(usize, usize, usize)> {
+        let pairs = |left: usize, right: usize| {
+            self.get_unchecked(left) == self.get_unchecked(right).complement()
+        };
+        let mut repeats = Vec::new();
+        for loop_start in 1..self.len {
+            for loop_len in 0..=max_loop.min(self.len - loop_start) {
+                let right_start = loop_start + loop_len;
+                if loop_len >= 2 && pairs(loop_start, right_start - 1) {
+                    continue;
+                }
+                let stem_len = (0..loop_start.min(self.len - right_start))
+                    .take_while(|&i| pairs(loop_start - 1 - i, right_start + i))
+                    .count();
+                if stem_len >= min_stem.max(1) {
+                    repeats.push((loop_start - stem_len, loop_start, stem_len));
+                }
+            }
+        }
+        repeats
+    }
+
+    /// Returns the longest substring that equals its own reverse complement,
+    /// such as the `GAATTC` EcoRI site, or `None` if there is none. Such
+    /// palindromes always have even length; ties go to the leftmost.
+    ///
+    /// Each gap between adjacent bases is expanded outwards for as long as
+    /// the bases on either side pair.
+    pub fn longest_palindrome(&self) -> Option<PackedDna> {
+        let (mut best_center, mut best_half) = (0, 0);
+        for center in 1..self.len {
+            let half = (0..center.min(self.len - center))
+                .take_while(|&i| {
+                    self.get_unchecked(center - 1 - i)
+                        == self.get_unchecked(center + i).complement()
+                })
+                .count();
+            if half > best_half {
+                best_center = center;
+                best_half = half;
+            }
+        }
+        if best_half == 0 {
+            return None;
+        }
+        Some(self.extract(best_center - best_half, best_center + best_half))
+    }
+
+    /// Tallies homopolymer runs by base and length, e.g. `AAACCG` has one
+    /// run each of `(A, 3)`, `(C, 2)` and `(G, 1)`.
+    pub fn run_length_histogram(&self) -> HashMap<(Nuc, usize), usize> {
+        let mut histogram = HashMap::new();
+        for run in self.runs() {
+            *histogram.entry(run).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Run-length encodes the sequence as maximal `(base, length)`
+    /// homopolymer runs, e.g. `AAACCG` gives `[(A, 3), (C, 2), (G, 1)]`.
+    pub fn rle(&self) -> Vec<(Nuc, usize)> {
+        self.runs().collect()
+    }
+
+    /// Rebuilds a sequence from `(base, length)` runs, the inverse of
+    /// [`rle`](Self::rle). Runs need not be maximal, and zero-length runs
+    /// add nothing.
+    pub fn from_rle(runs: &[(Nuc, usize)]) -> PackedDna {
+        runs.iter()
+            .flat_map(|&(nuc, len)| std::iter::repeat(nuc).take(len))
+            .collect()
+    }
+
+    /// The length of the longest homopolymer run, or 0 for an empty sequence.
+    pub fn longest_run(&self) -> usize {
+        self.runs().map(|(_, len)| len).max().unwrap_or(0)
+    }
+
+    /// Yields each maximal homopolymer run as a `(base, length)` pair.
+    pub(crate) fn runs(&self) -> impl Iterator<Item = (Nuc, usize)> + '_ {
+        let mut bases = self.iter().peekable();
+        std::iter::from_fn(move || {
+            let nuc = bases.next()?;
+            let mut len = 1;
+            while bases.next_if_eq(&nuc).is_some() {
+                len += 1;
+            }
+            Some((nuc, len))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dna(s: &str) -> PackedDna {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn tandem_repeats_dinucleotide() {
+        assert_eq!(dna("ATATATCG").tandem_repeats(2, 3), vec![(0, 3)]);
+    }
+
+    #[test]
+    fn rle_round_trip() {
+        let seq = dna("AAACCG");
+        let runs = seq.rle();
+        assert_eq!(runs, vec![(Nuc::A, 3), (Nuc::C, 2), (Nuc::G, 1)]);
+        assert_eq!(PackedDna::from_rle(&runs), seq);
+        assert!(dna("").rle().is_empty());
+        assert_eq!(
+            PackedDna::from_rle(&[(Nuc::T, 2), (Nuc::A, 0), (Nuc::T, 1)]),
+            dna("TTT")
+        );
+    }
+
+    #[test]
+    fn longest_run() {
+        assert_eq!(dna("ACCCGTTA").longest_run(), 3);
+        assert_eq!(dna("ACGT").longest_run(), 1);
+        assert_eq!(dna("").longest_run(), 0);
+    }
+
+    #[test]
+    fn run_length_histogram() {
+        let histogram = dna("AAACCG").run_length_histogram();
+        assert_eq!(histogram.len(), 3);
+        assert_eq!(histogram[&(Nuc::A, 3)], 1);
+        assert_eq!(histogram[&(Nuc::C, 2)], 1);
+        assert_eq!(histogram[&(Nuc::G, 1)], 1);
+    }
+
+    #[test]
+    fn run_length_histogram_repeated_runs() {
+        let histogram = dna("ATTATTA").run_length_histogram();
+        assert_eq!(histogram[&(Nuc::A, 1)], 3);
+        assert_eq!(histogram[&(Nuc::T, 2)], 2);
+        assert!(PackedDna::new().run_length_histogram().is_empty());
+    }
+
+    #[test]
+    fn inverted_repeats_hairpin() {
+        // stem ACGGT, loop AAA, then ACCGT (the stem's reverse complement)
+        let seq = dna("CCACGGTAAAACCGTCC");
+        assert_eq!(seq.inverted_repeats(4, 3), vec![(2, 7, 5)]);
+        assert_eq!(seq.inverted_repeats(6, 3), vec![]);
+        assert_eq!(seq.inverted_repeats(4, 2), vec![]);
+    }
+
+    #[test]
+    fn longest_palindrome() {
+        // the whole sequence is its own reverse complement
+        assert_eq!(
+            dna("TTGAATTCAA").longest_palindrome(),
+            Some(dna("TTGAATTCAA"))
+        );
+        assert_eq!(dna("ACGAATTCTT").longest_palindrome(), Some(dna("GAATTC")));
+        assert_eq!(dna("CCATGG").longest_palindrome(), Some(dna("CCATGG")));
+        assert_eq!(dna("AAGCTAT").longest_palindrome(), Some(dna("AGCT")));
+        assert_eq!(dna("AAAA").longest_palindrome(), None);
+        assert_eq!(dna("").longest_palindrome(), None);
+    }
+
+    #[test]
+    fn inverted_repeats_matches_reverse_complement() {
+        let seq = dna("CCGATGCATTTGCTA");
+        let repeats = seq.inverted_repeats(3, 4);
+        // ATG|CAT with no loop, and GCA|TT|TGC
+        assert_eq!(repeats, vec![(3, 6, 3), (5, 8, 3)]);
+        for (stem_start, loop_start, stem_len) in repeats {
+            let text = seq.to_string();
+            let stem = dna(&text[stem_start..loop_start]);
+            let loop_end = text.len() - stem_len;
+            let arm = (loop_start..=loop_end)
+                .map(|right| dna(&text[right..right + stem_len]))
+                .find(|arm| arm.is_reverse_complement_of(&stem));
+            assert!(arm.is_some(), "{} {} {}", stem_start, loop_start, stem_len);
+        }
+    }
+
+    #[test]
+    fn tandem_repeats_multiple_regions() {
+        let seq = dna("CAGCAGCAGTTACGACGACG");
+        assert_eq!(seq.tandem_repeats(3, 3), vec![(0, 3), (11, 3)]);
+        assert_eq!(seq.tandem_repeats(3, 4), vec![]);
+        assert_eq!(seq.tandem_repeats(0, 2), vec![]);
+        assert_eq!(dna("ACGT").tandem_repeats(2, 1), vec![]);
+    }
+}
@@ -0,0 +1,443 @@
+//! Pattern search within [`PackedDna`].
+
+use super::PackedDna;
+use crate::iupac::IupacNuc;
+
+/// The shortest adapter prefix [`PackedDna::trim_adapter`] will trim from the
+/// end of a read; shorter overlaps match by chance too often.
+pub const MIN_ADAPTER_OVERLAP: usize = 3;
+
+impl PackedDna {
+    /// Returns every start position at which the sequence matches `pattern`,
+    /// where each pattern position matches any base its IUPAC code stands
+    /// for. Matches may overlap; an empty pattern matches nowhere.
+    pub fn find_pattern(&self, pattern: &[IupacNuc]) -> Vec<usize> {
+        if pattern.is_empty() || pattern.len() > self.len {
+            return Vec::new();
+        }
+        (0..=self.len - pattern.len())
+            .filter(|&start| {
+                pattern
+                    .iter()
+                    .enumerate()
+                    .all(|(offset, code)| code.matches(self.get_unchecked(start + offset)))
+            })
+            .collect()
+    }
+
+    /// Returns every start position at which `needle` occurs exactly.
+    ///
+    /// Occurrences may overlap, so `AAAA` contains `AA` at 0, 1 and 2. An
+    /// empty needle matches nowhere.
+    pub fn find_all(&self, needle: &PackedDna) -> Vec<usize> {
+        self.find_approx(needle, 0)
+    }
+
+    /// Whether `other` is a cyclic rotation of `self`, as for two readings of
+    /// the same circular molecule from different origins.
+    ///
+    /// The sequences must be equal in length; `other` is then searched for
+    /// within `self` followed by itself. Two empty sequences are rotations
+    /// of each other.
+    pub fn is_rotation_of(&self, other: &PackedDna) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+        if self.is_empty() {
+            return true;
+        }
+        let mut doubled = self.clone();
+        doubled.append(self);
+        (0..self.len).any(|start| doubled.mismatches_at(other, start, 0) == 0)
+    }
+
+    /// Counts the occurrences of `needle`, including overlapping ones; this is
+    /// the number of positions returned by [`find_all`](Self::find_all).
+    ///
+    /// `AAAA` contains `AA` three times by this count but only twice by
+    /// [`count_non_overlapping`](Self::count_non_overlapping).
+    pub fn count_overlapping(&self, needle: &PackedDna) -> usize {
+        self.find_all(needle).len()
+    }
+
+    /// Counts the occurrences of `needle`, resuming the search after the end
+    /// of each match so that no two counted matches share a base.
+    ///
+    /// `AAAA` contains `AA` twice by this count but three times by
+    /// [`count_overlapping`](Self::count_overlapping).
+    pub fn count_non_overlapping(&self, needle: &PackedDna) -> usize {
+        let mut count = 0;
+        let mut next_free = 0;
+        for start in self.find_all(needle) {
+            if start >= next_free {
+                count += 1;
+                next_free = start + needle.len;
+            }
+        }
+        count
+    }
+
+    /// Returns every start position at which `needle` aligns with at most
+    /// `max_mismatches` substitutions (no insertions or deletions). Matches
+    /// may overlap; an empty needle matches nowhere.
+    pub fn find_approx(&self, needle: &PackedDna, max_mismatches: usize) -> Vec<usize> {
+        if needle.is_empty() || needle.len > self.len {
+            return Vec::new();
+        }
+        (0..=self.len - needle.len)
+            .filter(|&start| self.mismatches_at(needle, start, max_mismatches) <= max_mismatches)
+            .collect()
+    }
+
+    /// Returns the index of the barcode closest to `self` by Hamming distance,
+    /// if it is within `max_dist` mismatches, for demultiplexing reads.
+    ///
+    /// Only barcodes of the same length as `self` are considered. A read
+    /// equally close to two or more barcodes is ambiguous and gives `None`,
+    /// though a tie further away than the best match does not matter. Each
+    /// comparison stops as soon as it can no longer match the best barcode so
+    /// far.
+    pub fn nearest_barcode(&self, barcodes: &[PackedDna], max_dist: usize) -> Option<usize> {
+        let mut best: Option<(usize, usize)> = None;
+        let mut tied = false;
+        for (idx, barcode) in barcodes.iter().enumerate() {
+            if barcode.len != self.len {
+                continue;
+            }
+            let limit = best.map_or(max_dist, |(_, dist)| dist);
+            let dist = self.mismatches_at(barcode, 0, limit);
+            if dist > limit {
+                continue;
+            }
+            match best {
+                Some((_, best_dist)) if dist == best_dist => tied = true,
+                _ => {
+                    best = Some((idx, dist));
+                    tied = false;
+                }
+            }
+        }
+        if tied {
+            None
+        } else {
+            best.map(|(idx, _)| idx)
+        }
+    }
+
+    /// Scores every window against a position weight matrix, returning one
+    /// summed score per start position at which the whole matrix fits.
+    ///
+    /// Row `i` of `pwm` holds the log-odds scores for position `i` of the
+    /// motif, in A, C, G, T order. An empty matrix scores nowhere.
+    pub fn score_pwm(&self, pwm: &[[f64; 4]]) -> Vec<f64> {
+        if pwm.is_empty() || pwm.len() > self.len {
+            return Vec::new();
+        }
+        (0..=self.len - pwm.len())
+            .map(|start| {
+                pwm.iter()
+                    .enumerate()
+                    .map(|(offset, row)| {
+                        row[usize::from(self.get_unchecked(start + offset).to_bits())]
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Strips a 3' adapter, returning the read up to where the adapter
+    /// starts, or an unchanged copy if it is not found.
+    ///
+    /// Whole copies of `adapter` are located with
+    /// [`find_approx`](Self::find_approx), allowing up to `max_mismatches`
+    /// substitutions, and the one nearest the 3' end is used, so a chance
+    /// match early in the read cannot discard the rest of it. Failing that,
+    /// an adapter cut off by the end of the read is matched as the longest
+    /// read suffix, at least [`MIN_ADAPTER_OVERLAP`] bases long, that equals
+    /// a prefix of `adapter` within a proportional share of
+    /// `max_mismatches`.
+    pub fn trim_adapter(&self, adapter: &PackedDna, max_mismatches: usize) -> PackedDna {
+        if let Some(&start) = self.find_approx(adapter, max_mismatches).last() {
+            return self.extract(0, start);
+        }
+        let longest = adapter.len.saturating_sub(1).min(self.len);
+        let partial = (MIN_ADAPTER_OVERLAP..=longest).rev().find(|&overlap| {
+            let start = self.len - overlap;
+            let allowed = max_mismatches * overlap / adapter.len;
+            let mismatches = (0..overlap)
+                .filter(|&i| self.get_unchecked(start + i) != adapter.get_unchecked(i))
+                .count();
+            mismatches <= allowed
+        });
+        match partial {
+            Some(overlap) => self.extract(0, self.len - overlap),
+            None => self.clone(),
+        }
+    }
+
+    /// Counts mismatches between `needle` and the window at `start`, stopping
+    /// early once `limit` is exceeded.
+    fn mismatches_at(&self, needle: &PackedDna, start: usize, limit: usize) -> usize {
+        let mut mismatches = 0;
+        for (offset, nuc) in needle.iter().enumerate() {
+            if self.get_unchecked(start + offset) != nuc {
+                mismatches += 1;
+                if mismatches > limit {
+                    break;
+                }
+            }
+        }
+        mismatches
+    }
+
+    /// Returns the start positions of all suffixes in lexicographic order
+    /// (with A < C < G < T and a proper prefix sorting first).
+    ///
+    /// This is a straightforward comparison sort over the decoded bases, not
+    /// a linear-time construction.
+    pub fn suffix_array(&self) -> Vec<usize> {
+        let bases = self.to_bits_vec();
+        let mut suffixes: Vec<usize> = (0..self.len).collect();
+        suffixes.sort_unstable_by(|&a, &b| bases[a..].cmp(&bases[b..]));
+        suffixes
+    }
+
+    /// For each start position, the length of the shortest substring starting
+    /// there that occurs exactly once in the sequence, or 0 if even the whole
+    /// suffix from that position occurs elsewhere.
+    ///
+    /// A substring is unique exactly when it is longer than the longest
+    /// prefix its suffix shares with either neighbour in the
+    /// [`suffix_array`](Self::suffix_array); those shared prefix lengths come
+    /// from Kasai's algorithm.
+    pub fn shortest_unique_substrings(&self) -> Vec<usize> {
+        let bases = self.to_bits_vec();
+        let suffixes = self.suffix_array();
+        let mut rank = vec![0; self.len];
+        for (idx, &start) in suffixes.iter().enumerate() {
+            rank[start] = idx;
+        }
+        // shared[idx] is the common prefix of suffixes[idx - 1] and suffixes[idx]
+        let mut shared = vec![0; self.len + 1];
+        let mut common = 0;
+        for start in 0..self.len {
+            if rank[start] == 0 {
+                common = 0;
+                continue;
+            }
+            let previous = suffixes[rank[start] - 1];
+            while start + common < self.len
+                && previous + common < self.len
+                && bases[start + common] == bases[previous + common]
+            {
+                common += 1;
+            }
+            shared[rank[start]] = common;
+            common = common.saturating_sub(1);
+        }
+        let mut lengths = vec![0; self.len];
+        for (idx, &start) in suffixes.iter().enumerate() {
+            let needed = shared[idx].max(shared[idx + 1]) + 1;
+            if needed <= self.len - start {
+                lengths[start] = needed;
+            }
+        }
+        lengths
+    }
+
+    /// Decodes the sequence into one 2-bit code per byte, which orders the
+    /// same way as the bases.
+    pub(crate) fn to_bits_vec(&self) -> Vec<u8> {
+        self.iter().map(|nuc| nuc.to_bits()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dna(s: &str) -> PackedDna {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn find_pattern_purine_pyrimidine() {
+        let pattern = [IupacNuc::R, IupacNuc::Y];
+        assert_eq!(dna("AGCT").find_pattern(&pattern), vec![1]);
+        assert_eq!(dna("ACGT").find_pattern(&pattern), vec![0, 2]);
+    }
+
+    #[test]
+    fn find_all() {
+        assert_eq!(dna("AAAA").find_all(&dna("AA")), vec![0, 1, 2]);
+        assert_eq!(dna("ACGACG").find_all(&dna("ACG")), vec![0, 3]);
+        assert!(dna("ACGT").find_all(&dna("")).is_empty());
+    }
+
+    #[test]
+    fn overlapping_vs_non_overlapping_counts() {
+        let (haystack, needle) = (dna("AAAA"), dna("AA"));
+        assert_eq!(haystack.count_overlapping(&needle), 3);
+        assert_eq!(haystack.count_non_overlapping(&needle), 2);
+        assert_eq!(dna("AAAAA").count_non_overlapping(&needle), 2);
+        assert_eq!(needle.count_overlapping(&needle), 1);
+        assert_eq!(needle.count_non_overlapping(&needle), 1);
+    }
+
+    #[test]
+    fn trim_adapter() {
+        let adapter = dna("AGATCGGAAG");
+        let insert = dna("TTGCATGCCA");
+        let read = dna(&format!("{}{}GT", insert, adapter));
+        assert_eq!(read.trim_adapter(&adapter, 0), insert);
+
+        // one substitution inside the adapter
+        let read = dna(&format!("{}AGATCGTAAGGT", insert));
+        assert_eq!(read.trim_adapter(&adapter, 0), read);
+        assert_eq!(read.trim_adapter(&adapter, 1), insert);
+
+        assert_eq!(insert.trim_adapter(&adapter, 2), insert);
+    }
+
+    #[test]
+    fn trim_adapter_ignores_early_chance_match() {
+        let adapter = dna("AGATCGGAAG");
+        // within one mismatch of the adapter, near the 5' end
+        let insert = dna("CAGATCGCAAGTTGCATGCCA");
+        let read = dna(&format!("{}{}", insert, adapter));
+        assert_eq!(read.trim_adapter(&adapter, 1), insert);
+    }
+
+    #[test]
+    fn trim_adapter_partial_at_3prime_end() {
+        let adapter = dna("AGATCGGAAG");
+        let insert = dna("TTGCATGCCA");
+        let read = dna(&format!("{}AGATCG", insert));
+        assert_eq!(read.trim_adapter(&adapter, 0), insert);
+        // one mismatch is allowed over six bases with a budget of 2 in 10
+        let read = dna(&format!("{}AGTTCG", insert));
+        assert_eq!(read.trim_adapter(&adapter, 0), read);
+        assert_eq!(read.trim_adapter(&adapter, 2), insert);
+        // overlaps shorter than MIN_ADAPTER_OVERLAP are left alone
+        let read = dna(&format!("{}AG", insert));
+        assert_eq!(read.trim_adapter(&adapter, 0), read);
+    }
+
+    #[test]
+    fn is_rotation_of() {
+        assert!(dna("ACGT").is_rotation_of(&dna("GTAC")));
+        assert!(dna("ACGT").is_rotation_of(&dna("ACGT")));
+        assert!(!dna("ACGT").is_rotation_of(&dna("ACGA")));
+        assert!(!dna("ACGT").is_rotation_of(&dna("ACG")));
+        assert!(dna("").is_rotation_of(&dna("")));
+        let seq = dna("GATTACAGC");
+        for n in 0..seq.len() {
+            let mut rotated = seq.clone();
+            rotated.rotate_left(n);
+            assert!(rotated.is_rotation_of(&seq));
+        }
+    }
+
+    #[test]
+    fn find_approx() {
+        let seq = dna("ATGACG");
+        assert_eq!(seq.find_approx(&dna("ACG"), 1), vec![0, 3]);
+        assert_eq!(seq.find_approx(&dna("ACG"), 0), vec![3]);
+        assert_eq!(seq.find_approx(&dna("ACG"), 3), vec![0, 1, 2, 3]);
+        assert!(seq.find_approx(&dna(""), 1).is_empty());
+    }
+
+    #[test]
+    fn nearest_barcode() {
+        let barcodes = [dna("ACGTAC"), dna("TTGCAA"), dna("GGATCC"), dna("ACG")];
+        assert_eq!(dna("ACGTTC").nearest_barcode(&barcodes, 1), Some(0));
+        assert_eq!(dna("TTGCAA").nearest_barcode(&barcodes, 0), Some(1));
+        assert_eq!(dna("GGTTCC").nearest_barcode(&barcodes, 1), Some(2));
+        // two mismatches from both the first and third barcodes
+        assert_eq!(dna("ACATCC").nearest_barcode(&barcodes, 2), None);
+        assert_eq!(dna("ACGTAG").nearest_barcode(&barcodes, 0), None);
+        assert_eq!(dna("ACGTAG").nearest_barcode(&barcodes, 1), Some(0));
+        assert_eq!(dna("ACG").nearest_barcode(&barcodes, 0), Some(3));
+        assert_eq!(dna("ACGTAC").nearest_barcode(&[], 3), None);
+    }
+
+    #[test]
+    fn nearest_barcode_ties_are_ambiguous() {
+        let barcodes = [dna("AAAA"), dna("CCCC"), dna("AACC")];
+        // one mismatch from both AAAA and AACC
+        assert_eq!(dna("AAAC").nearest_barcode(&barcodes, 2), None);
+        assert_eq!(dna("ACCC").nearest_barcode(&barcodes, 2), None);
+        // CCCC and AACC tie, but only behind AAAA
+        assert_eq!(dna("ACAA").nearest_barcode(&barcodes, 3), Some(0));
+        let duplicated = [dna("ACGT"), dna("ACGT")];
+        assert_eq!(dna("ACGT").nearest_barcode(&duplicated, 0), None);
+    }
+
+    #[test]
+    fn score_pwm() {
+        // favours "TGA"
+        let pwm = [
+            [-1.0, -2.0, -2.0, 1.5],
+            [-1.0, -2.0, 2.0, -1.0],
+            [1.0, -0.5, -1.0, -1.0],
+        ];
+        let seq = dna("ATGAC");
+        // ATG: -1 - 1 - 1; TGA: 1.5 + 2 + 1; GAC: -2 - 1 - 0.5
+        assert_eq!(seq.score_pwm(&pwm), vec![-3.0, 4.5, -3.5]);
+        assert!(dna("TG").score_pwm(&pwm).is_empty());
+        assert!(seq.score_pwm(&[]).is_empty());
+    }
+
+    #[test]
+    fn suffix_array() {
+        let seq = dna("ACACAG");
+        let sa = seq.suffix_array();
+        assert_eq!(sa, vec![0, 2, 4, 1, 3, 5]);
+        let text = seq.to_string();
+        assert!(sa.windows(2).all(|pair| text[pair[0]..] < text[pair[1]..]));
+        assert!(dna("").suffix_array().is_empty());
+    }
+
+    #[test]
+    fn shortest_unique_substrings() {
+        // ACA occurs at both 0 and 2, so ACAC and ACAG are the shortest there
+        assert_eq!(
+            dna("ACACAG").shortest_unique_substrings(),
+            vec![4, 3, 4, 3, 2, 1]
+        );
+        assert_eq!(dna("AAAA").shortest_unique_substrings(), vec![4, 0, 0, 0]);
+        assert!(dna("").shortest_unique_substrings().is_empty());
+    }
+
+    #[test]
+    fn shortest_unique_substrings_match_brute_force() {
+        for text in ["ACGTACGTTACG", "GATTACAGATTACA", "CCCCGCCCC", "T"].iter() {
+            let seq = dna(text);
+            let occurrences = |sub: &str| {
+                (0..=text.len() - sub.len())
+                    .filter(|&i| text[i..].starts_with(sub))
+                    .count()
+            };
+            let expected: Vec<usize> = (0..text.len())
+                .map(|start| {
+                    (1..=text.len() - start)
+                        .find(|&len| occurrences(&text[start..start + len]) == 1)
+                        .unwrap_or(0)
+                })
+                .collect();
+            assert_eq!(seq.shortest_unique_substrings(), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn find_pattern_edge_cases() {
+        assert!(dna("ACGT").find_pattern(&[]).is_empty());
+        assert!(dna("A")
+            .find_pattern(&[IupacNuc::N, IupacNuc::N])
+            .is_empty());
+        assert_eq!(
+            dna("AAA").find_pattern(&[IupacNuc::N, IupacNuc::W]),
+            vec![0, 1]
+        );
+    }
+}
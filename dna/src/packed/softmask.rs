@@ -0,0 +1,128 @@
+//! Soft-masked sequences: [`PackedDna`] plus the lowercase regions of the
+//! text it was parsed from.
+
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use super::{PackedDna, ParseDnaError};
+use crate::Nuc;
+
+/// A sequence whose lowercase (soft-masked) regions, such as repeats in a
+/// genome assembly, are remembered alongside the packed bases.
+///
+/// A [`PackedDna`] only stores the bases, so parsing one discards case. This
+/// keeps the masked regions as sorted, non-overlapping, half-open
+/// `(start, end)` ranges. Derived equality compares both the bases and the
+/// mask; [`eq_ignoring_soft_mask`](Self::eq_ignoring_soft_mask) compares only
+/// the bases.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SoftMaskedDna {
+    seq: PackedDna,
+    masked: Vec<(usize, usize)>,
+}
+
+impl SoftMaskedDna {
+    /// The bases, without case.
+    pub fn seq(&self) -> &PackedDna {
+        &self.seq
+    }
+
+    /// The lowercase regions as half-open `(start, end)` ranges, in order.
+    pub fn masked_ranges(&self) -> &[(usize, usize)] {
+        &self.masked
+    }
+
+    /// Whether the base at `idx` was lowercase.
+    pub fn is_masked(&self, idx: usize) -> bool {
+        // the first range ending past `idx` is the only one that can hold it
+        let candidate = self.masked.partition_point(|&(_, end)| end <= idx);
+        self.masked
+            .get(candidate)
+            .map_or(false, |&(start, _)| start <= idx)
+    }
+
+    /// Whether both sequences hold the same bases, whatever their masks.
+    pub fn eq_ignoring_soft_mask(&self, other: &SoftMaskedDna) -> bool {
+        self.seq == other.seq
+    }
+
+    /// Discards the mask, keeping only the bases.
+    pub fn into_seq(self) -> PackedDna {
+        self.seq
+    }
+}
+
+impl FromStr for SoftMaskedDna {
+    type Err = ParseDnaError;
+
+    /// Parses a sequence, recording which bases are lowercase.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut soft = Self::default();
+        for (index, character) in s.chars().enumerate() {
+            let nuc = Nuc::try_from(character).map_err(|_| ParseDnaError { index, character })?;
+            soft.seq.push(nuc);
+            if character.is_ascii_lowercase() {
+                match soft.masked.last_mut() {
+                    Some(last) if last.1 == index => last.1 += 1,
+                    _ => soft.masked.push((index, index + 1)),
+                }
+            }
+        }
+        Ok(soft)
+    }
+}
+
+impl Display for SoftMaskedDna {
+    /// Prints the bases, lowercasing the masked regions.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ranges = self.masked.iter().peekable();
+        for (idx, nuc) in self.seq.iter().enumerate() {
+            while ranges.next_if(|&&(_, end)| end <= idx).is_some() {}
+            let character = char::from(nuc);
+            if ranges.peek().map_or(false, |&&(start, _)| start <= idx) {
+                write!(f, "{}", character.to_ascii_lowercase())?;
+            } else {
+                write!(f, "{}", character)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_masked_ranges() {
+        let soft: SoftMaskedDna = "ACgtaCGTt".parse().unwrap();
+        assert_eq!(soft.seq(), &"ACGTACGTT".parse::<PackedDna>().unwrap());
+        assert_eq!(soft.masked_ranges(), &[(2, 5), (8, 9)]);
+        assert!(soft.is_masked(2));
+        assert!(soft.is_masked(4));
+        assert!(!soft.is_masked(5));
+        assert!(soft.is_masked(8));
+        assert!(!soft.is_masked(9));
+        assert!(!soft.is_masked(0));
+        assert_eq!(soft.to_string(), "ACgtaCGTt");
+    }
+
+    #[test]
+    fn eq_ignoring_soft_mask() {
+        let masked: SoftMaskedDna = "ACgtACGT".parse().unwrap();
+        let unmasked: SoftMaskedDna = "ACGTACGT".parse().unwrap();
+        assert!(masked.eq_ignoring_soft_mask(&unmasked));
+        assert_ne!(masked, unmasked);
+        let other: SoftMaskedDna = "ACgtACGA".parse().unwrap();
+        assert!(!masked.eq_ignoring_soft_mask(&other));
+    }
+
+    #[test]
+    fn rejects_invalid_characters() {
+        let err = "acgn".parse::<SoftMaskedDna>().unwrap_err();
+        assert_eq!((err.index(), err.character()), (3, 'n'));
+    }
+}
@@ -0,0 +1,78 @@
+//! Export of [`PackedDna`] in the UCSC `.2bit` layout.
+
+use std::{
+    convert::TryFrom,
+    io::{self, Write},
+};
+
+use super::{PackedDna, BASES_PER_BYTE};
+use crate::Nuc;
+
+impl PackedDna {
+    /// Writes the sequence as a UCSC `.2bit` sequence record.
+    ///
+    /// This is a minimal subset of the format: the record holds the base
+    /// count, zero N-block and mask-block counts, a reserved word and the
+    /// packed bases. The file signature and sequence index are not written,
+    /// so multi-sequence files must be assembled by the caller. All integers
+    /// are little-endian.
+    ///
+    /// `.2bit` files use their own base encoding (`T = 00`, `C = 01`,
+    /// `A = 10`, `G = 11`) with the first base in the most significant bits
+    /// of each byte, so the bases are re-packed rather than copied.
+    pub fn write_2bit<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let dna_size = u32::try_from(self.len).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "sequence is too long for the .2bit format",
+            )
+        })?;
+        writer.write_all(&dna_size.to_le_bytes())?;
+        // N-block count, mask-block count, reserved
+        for _ in 0..3 {
+            writer.write_all(&0u32.to_le_bytes())?;
+        }
+        let mut packed = Vec::with_capacity(self.data.len());
+        let mut bases = self.iter().peekable();
+        while bases.peek().is_some() {
+            let byte = (0..BASES_PER_BYTE).fold(0, |byte, offset| {
+                let bits = bases.next().map_or(0, twobit_bits);
+                byte | bits << (2 * (BASES_PER_BYTE - 1 - offset))
+            });
+            packed.push(byte);
+        }
+        writer.write_all(&packed)
+    }
+}
+
+fn twobit_bits(nuc: Nuc) -> u8 {
+    match nuc {
+        Nuc::T => 0b00,
+        Nuc::C => 0b01,
+        Nuc::A => 0b10,
+        Nuc::G => 0b11,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_2bit_record() {
+        let dna: PackedDna = "TCAGA".parse().unwrap();
+        let mut out = Vec::new();
+        dna.write_2bit(&mut out).unwrap();
+        assert_eq!(out.len(), 16 + 2);
+        assert_eq!(u32::from_le_bytes([out[0], out[1], out[2], out[3]]), 5);
+        assert!(out[4..16].iter().all(|&b| b == 0));
+        assert_eq!(&out[16..], &[0b00_01_10_11, 0b10_00_00_00]);
+    }
+
+    #[test]
+    fn write_2bit_empty() {
+        let mut out = Vec::new();
+        PackedDna::new().write_2bit(&mut out).unwrap();
+        assert_eq!(out, vec![0; 16]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Amino acids and the standard genetic code.
+
+use crate::{
+    iupac::{IupacDna, IupacNuc},
+    Nuc,
+};
+
+/// The standard genetic code, one-letter amino acid codes with `*` for stop,
+/// for codons in `TCAG` order (`TTT`, `TTC`, `TTA`, `TTG`, `TCT`, ...).
+const STANDARD_CODE: &[u8; 64] =
+    b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+/// An amino acid, or the stop signal that ends translation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AminoAcid {
+    /// Alanine (A)
+    Ala,
+    /// Arginine (R)
+    Arg,
+    /// Asparagine (N)
+    Asn,
+    /// Aspartic acid (D)
+    Asp,
+    /// Cysteine (C)
+    Cys,
+    /// Glutamine (Q)
+    Gln,
+    /// Glutamic acid (E)
+    Glu,
+    /// Glycine (G)
+    Gly,
+    /// Histidine (H)
+    His,
+    /// Isoleucine (I)
+    Ile,
+    /// Leucine (L)
+    Leu,
+    /// Lysine (K)
+    Lys,
+    /// Methionine (M)
+    Met,
+    /// Phenylalanine (F)
+    Phe,
+    /// Proline (P)
+    Pro,
+    /// Serine (S)
+    Ser,
+    /// Threonine (T)
+    Thr,
+    /// Tryptophan (W)
+    Trp,
+    /// Tyrosine (Y)
+    Tyr,
+    /// Valine (V)
+    Val,
+    /// A stop codon (*)
+    Stop,
+}
+
+impl AminoAcid {
+    /// Translates a codon with the standard genetic code.
+    pub fn from_codon(codon: [Nuc; 3]) -> Self {
+        let tcag = |nuc: Nuc| match nuc {
+            Nuc::T => 0,
+            Nuc::C => 1,
+            Nuc::A => 2,
+            Nuc::G => 3,
+        };
+        let idx = 16 * tcag(codon[0]) + 4 * tcag(codon[1]) + tcag(codon[2]);
+        Self::from_letter(STANDARD_CODE[idx])
+    }
+
+    /// Looks up an uppercase one-letter code, or `*` for stop.
+    fn from_letter(letter: u8) -> Self {
+        match letter {
+            b'A' => Self::Ala,
+            b'R' => Self::Arg,
+            b'N' => Self::Asn,
+            b'D' => Self::Asp,
+            b'C' => Self::Cys,
+            b'Q' => Self::Gln,
+            b'E' => Self::Glu,
+            b'G' => Self::Gly,
+            b'H' => Self::His,
+            b'I' => Self::Ile,
+            b'L' => Self::Leu,
+            b'K' => Self::Lys,
+            b'M' => Self::Met,
+            b'F' => Self::Phe,
+            b'P' => Self::Pro,
+            b'S' => Self::Ser,
+            b'T' => Self::Thr,
+            b'W' => Self::Trp,
+            b'Y' => Self::Tyr,
+            b'V' => Self::Val,
+            b'*' => Self::Stop,
+            _ => unreachable!("not an amino acid code: {}", char::from(letter)),
+        }
+    }
+}
+
+/// Reverse-translates a protein into a degenerate DNA sequence, for example
+/// to design degenerate primers.
+///
+/// Each amino acid becomes the single IUPAC codon whose positions are the
+/// union of the bases at that position across all its codons in the
+/// standard code. This is exact for most amino acids (alanine is `GCN`), but
+/// for the six-codon leucine, serine and arginine it also admits codons of
+/// other amino acids: leucine's `TTR` and `CTN` combine to `YTN`.
+pub fn reverse_translate(aas: &[AminoAcid]) -> IupacDna {
+    let mut dna = IupacDna::new();
+    for &aa in aas {
+        let mut masks = [0; 3];
+        for code in 0..64u8 {
+            let codon = [
+                Nuc::from_bits(code >> 4),
+                Nuc::from_bits(code >> 2),
+                Nuc::from_bits(code),
+            ];
+            if AminoAcid::from_codon(codon) == aa {
+                for (mask, nuc) in masks.iter_mut().zip(codon.iter()) {
+                    *mask |= 1 << nuc.to_bits();
+                }
+            }
+        }
+        for &mask in masks.iter() {
+            dna.push(IupacNuc::from_mask(mask).expect("every amino acid has a codon"));
+        }
+    }
+    dna
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codon(s: &str) -> [Nuc; 3] {
+        let mut nucs = s.parse::<crate::packed::PackedDna>().unwrap().into_iter();
+        [
+            nucs.next().unwrap(),
+            nucs.next().unwrap(),
+            nucs.next().unwrap(),
+        ]
+    }
+
+    #[test]
+    fn from_codon() {
+        assert_eq!(AminoAcid::from_codon(codon("ATG")), AminoAcid::Met);
+        assert_eq!(AminoAcid::from_codon(codon("TGG")), AminoAcid::Trp);
+        assert_eq!(AminoAcid::from_codon(codon("TTA")), AminoAcid::Leu);
+        assert_eq!(AminoAcid::from_codon(codon("AGA")), AminoAcid::Arg);
+        assert_eq!(AminoAcid::from_codon(codon("TGA")), AminoAcid::Stop);
+        assert_eq!(AminoAcid::from_codon(codon("GGC")), AminoAcid::Gly);
+    }
+
+    #[test]
+    fn reverse_translate_met_and_leu() {
+        assert_eq!(reverse_translate(&[AminoAcid::Met]).to_string(), "ATG");
+        assert_eq!(reverse_translate(&[AminoAcid::Leu]).to_string(), "YTN");
+    }
+
+    #[test]
+    fn reverse_translate_peptide() {
+        let aas = [
+            AminoAcid::Met,
+            AminoAcid::Ala,
+            AminoAcid::Phe,
+            AminoAcid::Ser,
+            AminoAcid::Arg,
+            AminoAcid::Stop,
+        ];
+        assert_eq!(reverse_translate(&aas).to_string(), "ATGGCNTTYWSNMGNTRR");
+        assert!(reverse_translate(&[]).is_empty());
+    }
+
+    #[test]
+    fn reverse_translation_covers_every_codon() {
+        for code in 0..64u8 {
+            let codon = [
+                Nuc::from_bits(code >> 4),
+                Nuc::from_bits(code >> 2),
+                Nuc::from_bits(code),
+            ];
+            let degenerate = reverse_translate(&[AminoAcid::from_codon(codon)]);
+            assert!(degenerate
+                .iter()
+                .zip(codon.iter())
+                .all(|(iupac, &nuc)| iupac.matches(nuc)));
+        }
+    }
+}
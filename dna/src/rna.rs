@@ -0,0 +1,219 @@
+//! RNA alignment rows, with gaps, packed three bits to a base.
+
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display},
+    iter::FromIterator,
+    str::FromStr,
+};
+
+use crate::{
+    packed::{PackedDna, PackedSeq, ParseDnaError},
+    Nuc, ParseNucError,
+};
+
+/// A ribonucleotide or an alignment gap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RnaNuc {
+    /// Adenine
+    A,
+    /// Cytosine
+    C,
+    /// Guanine
+    G,
+    /// Uracil
+    U,
+    /// A gap in an alignment row
+    Gap,
+}
+
+impl RnaNuc {
+    /// The 3-bit code used when packing this base: A, C, G and U share the
+    /// 2-bit codes of A, C, G and T, and a gap is `100`.
+    fn to_code(self) -> u8 {
+        match self {
+            Self::A => 0b000,
+            Self::C => 0b001,
+            Self::G => 0b010,
+            Self::U => 0b011,
+            Self::Gap => 0b100,
+        }
+    }
+
+    /// Decodes a 3-bit code, or `None` for the unused codes `101` to `111`.
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0b000 => Some(Self::A),
+            0b001 => Some(Self::C),
+            0b010 => Some(Self::G),
+            0b011 => Some(Self::U),
+            0b100 => Some(Self::Gap),
+            _ => None,
+        }
+    }
+}
+
+/// Transcribes a DNA nucleotide, T becoming U.
+impl From<Nuc> for RnaNuc {
+    fn from(nuc: Nuc) -> Self {
+        match nuc {
+            Nuc::A => Self::A,
+            Nuc::C => Self::C,
+            Nuc::G => Self::G,
+            Nuc::T => Self::U,
+        }
+    }
+}
+
+impl From<RnaNuc> for char {
+    fn from(nuc: RnaNuc) -> Self {
+        match nuc {
+            RnaNuc::A => 'A',
+            RnaNuc::C => 'C',
+            RnaNuc::G => 'G',
+            RnaNuc::U => 'U',
+            RnaNuc::Gap => '-',
+        }
+    }
+}
+
+impl Display for RnaNuc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", char::from(*self))
+    }
+}
+
+impl TryFrom<char> for RnaNuc {
+    type Error = ParseNucError<char>;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value.to_ascii_uppercase() {
+            'A' => Ok(Self::A),
+            'C' => Ok(Self::C),
+            'G' => Ok(Self::G),
+            'U' => Ok(Self::U),
+            '-' => Ok(Self::Gap),
+            _ => Err(ParseNucError(value)),
+        }
+    }
+}
+
+/// An RNA alignment row, packed as 3-bit codes.
+///
+/// Code `i` occupies bits `3 * i` up to `3 * i + 3` of the buffer, so some
+/// codes straddle two bytes (see [`PackedSeq`]).
+pub type GappedRna = PackedSeq<3>;
+
+impl GappedRna {
+    /// Appends a base or gap to the end of the row.
+    pub fn push(&mut self, nuc: RnaNuc) {
+        self.push_code(nuc.to_code());
+    }
+
+    /// Returns the base or gap at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn get(&self, idx: usize) -> RnaNuc {
+        RnaNuc::from_code(self.code(idx)).expect("packed codes are always assigned")
+    }
+
+    /// Replaces the base or gap at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn set(&mut self, idx: usize, nuc: RnaNuc) {
+        self.set_code(idx, nuc.to_code());
+    }
+
+    /// Returns an iterator over the bases and gaps in the row.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = RnaNuc> + ExactSizeIterator + '_ {
+        (0..self.len()).map(move |idx| self.get(idx))
+    }
+}
+
+impl FromStr for GappedRna {
+    type Err = ParseDnaError;
+
+    /// Parses a row of A, C, G, U and `-`, case insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars()
+            .enumerate()
+            .map(|(index, character)| {
+                RnaNuc::try_from(character).map_err(|_| ParseDnaError::new(index, character))
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<RnaNuc> for GappedRna {
+    fn from_iter<I: IntoIterator<Item = RnaNuc>>(iter: I) -> Self {
+        let mut rna = Self::new();
+        for nuc in iter {
+            rna.push(nuc);
+        }
+        rna
+    }
+}
+
+/// Transcribes a DNA sequence, T becoming U.
+impl From<&PackedDna> for GappedRna {
+    fn from(dna: &PackedDna) -> Self {
+        dna.iter().map(RnaNuc::from).collect()
+    }
+}
+
+impl Display for GappedRna {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for nuc in self.iter() {
+            write!(f, "{}", nuc)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for code in 0..8 {
+            if let Some(nuc) = RnaNuc::from_code(code) {
+                assert_eq!(nuc.to_code(), code);
+            } else {
+                assert!(code > 0b100);
+            }
+        }
+    }
+
+    #[test]
+    fn gapped_rna_round_trip() {
+        let rna: GappedRna = "acgu-GU--A".parse().unwrap();
+        assert_eq!(rna.len(), 10);
+        // 30 bits, with codes 2 and 5 straddling byte boundaries
+        assert_eq!(rna.byte_len(), 4);
+        assert_eq!(rna.get(2), RnaNuc::G);
+        assert_eq!(rna.get(4), RnaNuc::Gap);
+        assert_eq!(rna.get(5), RnaNuc::G);
+        assert_eq!(rna.to_string(), "ACGU-GU--A");
+        assert_eq!("ACTG".parse::<GappedRna>().unwrap_err().index(), 2);
+    }
+
+    #[test]
+    fn set_replaces_straddling_codes() {
+        let mut rna: GappedRna = "AAAAAAAA".parse().unwrap();
+        rna.set(2, RnaNuc::Gap);
+        rna.set(5, RnaNuc::U);
+        assert_eq!(rna.to_string(), "AA-AAUAA");
+        assert_eq!(rna, "AA-AAUAA".parse().unwrap());
+    }
+
+    #[test]
+    fn transcribes_packed_dna() {
+        let dna: PackedDna = "ATGCT".parse().unwrap();
+        assert_eq!(GappedRna::from(&dna).to_string(), "AUGCU");
+    }
+}
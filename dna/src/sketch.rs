@@ -0,0 +1,121 @@
+//! Working with MinHash sketches built by [`PackedDna::minhash`].
+//!
+//! [`PackedDna::minhash`]: crate::packed::PackedDna::minhash
+
+use std::{cmp::Ordering, convert::TryInto, mem::size_of};
+
+/// An error returned by [`sketch_from_bytes`] when the input is not a whole
+/// number of `u64` hashes.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("sketch of {byte_len} bytes is not a whole number of 8-byte hashes")]
+pub struct SketchError {
+    byte_len: usize,
+}
+
+impl SketchError {
+    /// The length of the rejected input, in bytes.
+    pub fn byte_len(&self) -> usize {
+        self.byte_len
+    }
+}
+
+/// Serializes a sketch as its hashes in order, each a little-endian `u64`.
+pub fn sketch_to_bytes(sketch: &[u64]) -> Vec<u8> {
+    sketch.iter().flat_map(|hash| hash.to_le_bytes()).collect()
+}
+
+/// Reads back a sketch written by [`sketch_to_bytes`].
+pub fn sketch_from_bytes(bytes: &[u8]) -> Result<Vec<u64>, SketchError> {
+    if bytes.len() % size_of::<u64>() != 0 {
+        return Err(SketchError {
+            byte_len: bytes.len(),
+        });
+    }
+    Ok(bytes
+        .chunks_exact(size_of::<u64>())
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+/// Estimates the Jaccard index of the `k`-mer sets behind two bottom-`n`
+/// sketches.
+///
+/// The estimate looks at the smallest `n` distinct hashes of the two
+/// sketches combined, where `n` is the size of the smaller sketch, and
+/// returns the fraction of those present in both. Two empty sketches are
+/// considered identical.
+pub fn similarity(a: &[u64], b: &[u64]) -> f64 {
+    let size = a.len().min(b.len());
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if size == 0 {
+        return 0.0;
+    }
+    let (mut i, mut j) = (0, 0);
+    let (mut seen, mut shared) = (0, 0);
+    while seen < size && i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+        seen += 1;
+    }
+    shared as f64 / seen as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packed::PackedDna;
+
+    fn dna(s: &str) -> PackedDna {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn minhash_is_deterministic() {
+        let seq = dna("ACGTTGCAAGCTTAGGCATCGATCGGA");
+        let sketch = seq.minhash(5, 8);
+        assert_eq!(sketch.len(), 8);
+        assert_eq!(sketch, seq.clone().minhash(5, 8));
+        assert!(sketch.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn similarity_with_itself_is_one() {
+        let sketch = dna("ACGTTGCAAGCTTAGGCATCGATCGGA").minhash(5, 8);
+        assert_eq!(similarity(&sketch, &sketch), 1.0);
+        assert_eq!(similarity(&[], &[]), 1.0);
+    }
+
+    #[test]
+    fn sketch_bytes_round_trip() {
+        let sketch = dna("ACGTTGCAAGCTTAGGCATCGATCGGA").minhash(5, 8);
+        let bytes = sketch_to_bytes(&sketch);
+        assert_eq!(bytes.len(), 64);
+        assert_eq!(sketch_from_bytes(&bytes), Ok(sketch));
+        assert_eq!(sketch_to_bytes(&[1]), vec![1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(sketch_from_bytes(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn sketch_from_bytes_rejects_partial_hashes() {
+        let bytes = sketch_to_bytes(&[1, 2]);
+        let err = sketch_from_bytes(&bytes[..12]).unwrap_err();
+        assert_eq!(err.byte_len(), 12);
+    }
+
+    #[test]
+    fn similarity_of_unrelated_sequences() {
+        let a = dna("AAAAAAAAAAAA").minhash(4, 8);
+        let b = dna("CCCCCCCCCCCC").minhash(4, 8);
+        assert_eq!(similarity(&a, &b), 0.0);
+        assert_eq!(similarity(&[1, 2, 3, 4], &[1, 2, 5, 6]), 0.5);
+    }
+}
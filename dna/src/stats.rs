@@ -0,0 +1,59 @@
+//! Sequencing statistics that are not tied to a single sequence.
+
+/// Computes per-position depth over a reference of `len` bases from
+/// half-open `(start, end)` aligned intervals.
+///
+/// Intervals are clamped to the reference, so parts outside `0..len` are
+/// ignored, as are empty or reversed intervals.
+pub fn coverage(len: usize, intervals: &[(usize, usize)]) -> Vec<u32> {
+    // difference array: +1 where an interval starts, -1 just past its end
+    let mut deltas = vec![0i64; len + 1];
+    for &(start, end) in intervals {
+        let end = end.min(len);
+        if start < end {
+            deltas[start] += 1;
+            deltas[end] -= 1;
+        }
+    }
+    let mut depth = 0;
+    deltas[..len]
+        .iter()
+        .map(|delta| {
+            depth += delta;
+            depth as u32
+        })
+        .collect()
+}
+
+/// The expected mean depth from sequencing `num_reads` reads of `read_len`
+/// bases over a genome of `genome_len` bases, as in the Lander-Waterman
+/// model: `num_reads * read_len / genome_len`. An empty genome gives 0.0.
+pub fn expected_coverage(genome_len: usize, read_len: usize, num_reads: usize) -> f64 {
+    if genome_len == 0 {
+        return 0.0;
+    }
+    num_reads as f64 * read_len as f64 / genome_len as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage_overlapping_intervals() {
+        assert_eq!(coverage(8, &[(1, 5), (3, 7)]), vec![0, 1, 1, 2, 2, 1, 1, 0]);
+    }
+
+    #[test]
+    fn coverage_clamps_intervals() {
+        assert_eq!(coverage(4, &[(2, 10), (6, 9), (3, 1)]), vec![0, 0, 1, 1]);
+        assert!(coverage(0, &[(0, 3)]).is_empty());
+    }
+
+    #[test]
+    fn expected_coverage() {
+        assert_eq!(super::expected_coverage(1_000_000, 150, 200_000), 30.0);
+        assert_eq!(super::expected_coverage(100, 10, 5), 0.5);
+        assert_eq!(super::expected_coverage(0, 150, 1000), 0.0);
+    }
+}